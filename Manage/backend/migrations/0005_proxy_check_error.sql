@@ -0,0 +1,2 @@
+-- 记录节点最近一次检测失败的原因
+ALTER TABLE proxy_nodes ADD COLUMN last_error TEXT;
@@ -0,0 +1,9 @@
+-- 分组定时检测设置，未配置的分组使用默认间隔
+CREATE TABLE IF NOT EXISTS group_check_settings (
+    group_name TEXT PRIMARY KEY,
+    check_interval_minutes INTEGER NOT NULL DEFAULT 30,   -- 检测间隔（分钟），0 表示不参与定时检测
+    updated_at TEXT DEFAULT (datetime('now'))
+);
+
+-- 定时检测按 last_check_at 筛选到期节点
+CREATE INDEX IF NOT EXISTS idx_proxy_nodes_last_check ON proxy_nodes(last_check_at);
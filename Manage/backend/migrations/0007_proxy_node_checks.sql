@@ -0,0 +1,27 @@
+-- 节点检测历史，每次检测写入一条
+CREATE TABLE IF NOT EXISTS proxy_node_checks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    node_id INTEGER NOT NULL,
+    status TEXT NOT NULL,            -- valid, invalid
+    latency INTEGER,                 -- 延迟（毫秒）
+    message TEXT,                    -- 检测结果说明 / 失败原因
+    checked_at TEXT DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_proxy_node_checks_node_time ON proxy_node_checks(node_id, checked_at);
+CREATE INDEX IF NOT EXISTS idx_proxy_node_checks_time ON proxy_node_checks(checked_at);
+
+-- 超过保留期的检测历史按小时聚合
+CREATE TABLE IF NOT EXISTS proxy_node_check_hourly (
+    node_id INTEGER NOT NULL,
+    hour TEXT NOT NULL,              -- YYYY-MM-DD HH:00:00
+    total_checks INTEGER NOT NULL DEFAULT 0,
+    valid_checks INTEGER NOT NULL DEFAULT 0,
+    latency_sum INTEGER NOT NULL DEFAULT 0,
+    latency_count INTEGER NOT NULL DEFAULT 0,
+    latency_min INTEGER,
+    latency_max INTEGER,
+    PRIMARY KEY (node_id, hour)
+);
+
+CREATE INDEX IF NOT EXISTS idx_proxy_node_check_hourly_hour ON proxy_node_check_hourly(hour);
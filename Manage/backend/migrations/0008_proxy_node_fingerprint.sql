@@ -0,0 +1,5 @@
+-- 节点指纹：协议、地址、端口、凭据与传输参数的哈希，导入时用于去重
+-- 旧数据的指纹在导入或调用 POST /api/proxy/nodes/migrate-config 时补齐
+ALTER TABLE proxy_nodes ADD COLUMN fingerprint TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_proxy_nodes_fingerprint ON proxy_nodes(fingerprint);
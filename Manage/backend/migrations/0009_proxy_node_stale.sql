@@ -0,0 +1,2 @@
+-- 订阅刷新时上游已不再提供的节点，记录标记失效的时间
+ALTER TABLE proxy_nodes ADD COLUMN stale_at TEXT;
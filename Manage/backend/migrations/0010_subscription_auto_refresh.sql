@@ -0,0 +1,9 @@
+-- 订阅定时刷新设置与最近一次刷新结果
+ALTER TABLE subscription_info ADD COLUMN refresh_enabled INTEGER DEFAULT 0;
+ALTER TABLE subscription_info ADD COLUMN refresh_interval_minutes INTEGER DEFAULT 1440;
+ALTER TABLE subscription_info ADD COLUMN last_refresh_at TEXT;
+ALTER TABLE subscription_info ADD COLUMN last_refresh_status TEXT;   -- success, error
+ALTER TABLE subscription_info ADD COLUMN last_refresh_added INTEGER;
+ALTER TABLE subscription_info ADD COLUMN last_refresh_updated INTEGER;
+ALTER TABLE subscription_info ADD COLUMN last_refresh_vanished INTEGER;
+ALTER TABLE subscription_info ADD COLUMN last_refresh_error TEXT;
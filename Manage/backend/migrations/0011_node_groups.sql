@@ -0,0 +1,83 @@
+-- 节点分组，proxy_nodes / subscription_info / group_check_settings 通过 group_id 关联，分组改名只需更新 node_groups
+CREATE TABLE IF NOT EXISTS node_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    color TEXT,                      -- 显示颜色，如 #1890ff
+    sort_order INTEGER DEFAULT 0,
+    created_at TEXT DEFAULT (datetime('now')),
+    updated_at TEXT DEFAULT (datetime('now'))
+);
+
+-- 迁移已有的分组名称（空名称表示未分组）
+INSERT OR IGNORE INTO node_groups (name)
+SELECT DISTINCT group_name FROM proxy_nodes WHERE COALESCE(group_name, '') != '';
+
+INSERT OR IGNORE INTO node_groups (name)
+SELECT group_name FROM subscription_info WHERE COALESCE(group_name, '') != '';
+
+INSERT OR IGNORE INTO node_groups (name)
+SELECT group_name FROM group_check_settings WHERE group_name != '';
+
+-- 节点所在分组，为空表示未分组
+ALTER TABLE proxy_nodes ADD COLUMN group_id INTEGER REFERENCES node_groups(id) ON DELETE SET NULL;
+
+UPDATE proxy_nodes
+SET group_id = (SELECT g.id FROM node_groups g WHERE g.name = proxy_nodes.group_name)
+WHERE COALESCE(group_name, '') != '';
+
+DROP INDEX IF EXISTS idx_proxy_nodes_group;
+ALTER TABLE proxy_nodes DROP COLUMN group_name;
+
+CREATE INDEX IF NOT EXISTS idx_proxy_nodes_group_id ON proxy_nodes(group_id);
+
+-- 订阅信息：每个分组至多一条，随分组一起删除；未分组的订阅信息无法关联，不再保留
+CREATE TABLE subscription_info_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    group_id INTEGER NOT NULL UNIQUE REFERENCES node_groups(id) ON DELETE CASCADE,
+    subscription_url TEXT,
+    upload_bytes INTEGER DEFAULT 0,
+    download_bytes INTEGER DEFAULT 0,
+    total_bytes INTEGER DEFAULT 0,
+    expire_timestamp INTEGER,
+    last_update_at TEXT DEFAULT (datetime('now')),
+    created_at TEXT DEFAULT (datetime('now')),
+    refresh_enabled INTEGER DEFAULT 0,
+    refresh_interval_minutes INTEGER DEFAULT 1440,
+    last_refresh_at TEXT,
+    last_refresh_status TEXT,   -- success, error
+    last_refresh_added INTEGER,
+    last_refresh_updated INTEGER,
+    last_refresh_vanished INTEGER,
+    last_refresh_error TEXT
+);
+
+INSERT INTO subscription_info_new (
+    id, group_id, subscription_url, upload_bytes, download_bytes, total_bytes, expire_timestamp, last_update_at, created_at,
+    refresh_enabled, refresh_interval_minutes, last_refresh_at, last_refresh_status,
+    last_refresh_added, last_refresh_updated, last_refresh_vanished, last_refresh_error
+)
+SELECT
+    s.id, g.id, s.subscription_url, s.upload_bytes, s.download_bytes, s.total_bytes, s.expire_timestamp, s.last_update_at, s.created_at,
+    s.refresh_enabled, s.refresh_interval_minutes, s.last_refresh_at, s.last_refresh_status,
+    s.last_refresh_added, s.last_refresh_updated, s.last_refresh_vanished, s.last_refresh_error
+FROM subscription_info s JOIN node_groups g ON g.name = s.group_name;
+
+DROP TABLE subscription_info;
+ALTER TABLE subscription_info_new RENAME TO subscription_info;
+
+-- 分组检测设置：group_id 为空的一行对应未分组的节点，随分组一起删除
+CREATE TABLE group_check_settings_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    group_id INTEGER UNIQUE REFERENCES node_groups(id) ON DELETE CASCADE,
+    check_interval_minutes INTEGER NOT NULL DEFAULT 30,   -- 检测间隔（分钟），0 表示不参与定时检测
+    updated_at TEXT DEFAULT (datetime('now'))
+);
+
+INSERT INTO group_check_settings_new (group_id, check_interval_minutes, updated_at)
+SELECT g.id, s.check_interval_minutes, s.updated_at
+FROM group_check_settings s LEFT JOIN node_groups g ON g.name = s.group_name
+WHERE s.group_name = '' OR g.id IS NOT NULL;
+
+DROP TABLE group_check_settings;
+ALTER TABLE group_check_settings_new RENAME TO group_check_settings;
@@ -0,0 +1,5 @@
+-- 节点标签（JSON 字符串数组）与地区代码（ISO 3166-1 alpha-2，导入时从名称识别）
+ALTER TABLE proxy_nodes ADD COLUMN tags TEXT DEFAULT '[]';
+ALTER TABLE proxy_nodes ADD COLUMN region TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_proxy_nodes_region ON proxy_nodes(region);
@@ -0,0 +1,8 @@
+-- 节点启用状态（禁用的节点不参与检测与导出）与手动排序
+ALTER TABLE proxy_nodes ADD COLUMN enabled INTEGER DEFAULT 1;
+ALTER TABLE proxy_nodes ADD COLUMN sort_order INTEGER DEFAULT 0;
+
+-- 已有节点按创建顺序排列
+UPDATE proxy_nodes SET sort_order = id;
+
+CREATE INDEX IF NOT EXISTS idx_proxy_nodes_group_sort ON proxy_nodes(group_id, sort_order);
@@ -0,0 +1,2 @@
+-- 0013 添加的 enabled 列允许 NULL，统一视为启用
+UPDATE proxy_nodes SET enabled = 1 WHERE enabled IS NULL;
@@ -0,0 +1,7 @@
+-- 一次性数据迁移（如 POST /api/proxy/nodes/migrate-config）的执行记录，全部成功后不再重复执行
+CREATE TABLE IF NOT EXISTS data_migrations (
+    name TEXT PRIMARY KEY,
+    migrated INTEGER DEFAULT 0,
+    failed INTEGER DEFAULT 0,
+    completed_at TEXT DEFAULT (datetime('now'))
+);
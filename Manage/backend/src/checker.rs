@@ -0,0 +1,447 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::Duration;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use futures_util::future::{select, Either};
+use futures_util::stream::{self, StreamExt};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use worker::*;
+use crate::models::{ProxyNode, ProxyCheckResult};
+use crate::protocol::{self, Protocol, NodeConfig};
+
+// 单个节点的 TCP 连接超时（毫秒）
+pub const CONNECT_TIMEOUT_MS: u64 = 5000;
+// 协议握手阶段的超时（毫秒）
+pub const HANDSHAKE_TIMEOUT_MS: u64 = 5000;
+
+// 批量检测的并发数，Workers 单个请求最多同时打开 6 个出站连接
+pub const CHECK_CONCURRENCY: usize = 6;
+// 手动批量检测的时间预算（毫秒），超出后返回已完成的部分结果
+pub const REQUEST_CHECK_BUDGET_MS: f64 = 25_000.0;
+
+// HTTP CONNECT 探测使用的目标地址
+const PROBE_TARGET: &str = "www.gstatic.com:443";
+
+// 节点检测结果
+pub struct CheckOutcome {
+    pub status: &'static str,
+    pub latency: Option<i64>,
+    pub message: String,
+}
+
+impl CheckOutcome {
+    fn valid(latency: i64, message: String) -> Self {
+        Self {
+            status: "valid",
+            latency: Some(latency),
+            message,
+        }
+    }
+
+    fn invalid(message: String) -> Self {
+        Self {
+            status: "invalid",
+            latency: None,
+            message,
+        }
+    }
+
+    // 无法检测的节点保持 unknown 状态
+    fn unchecked(message: String) -> Self {
+        Self {
+            status: "unknown",
+            latency: None,
+            message,
+        }
+    }
+
+    // 失败原因，检测成功或未检测时为 None
+    pub fn error(&self) -> Option<&str> {
+        if self.status == "invalid" {
+            Some(&self.message)
+        } else {
+            None
+        }
+    }
+}
+
+// 握手探测方式
+enum Probe {
+    // 只验证 TCP 端口可连通（ss、未启用 TLS 的 vmess 等无法在 Worker 内完成握手）
+    TcpOnly,
+    HttpConnect,
+    Socks5,
+    Tls(TlsParams),
+}
+
+// TLS 握手参数
+struct TlsParams {
+    sni: String,
+    alpn: Vec<String>,
+}
+
+// 通过 Workers sockets API 对节点发起真实的 TCP 连接，测量建连延迟，
+// 并根据协议继续完成握手验证
+pub async fn check_proxy_node(node: &ProxyNode) -> CheckOutcome {
+    let host = node.address.trim();
+    if host.is_empty() {
+        return CheckOutcome::invalid("节点地址为空".to_string());
+    }
+
+    let port = match u16::try_from(node.port) {
+        Ok(p) if p > 0 => p,
+        _ => return CheckOutcome::invalid(format!("端口无效: {}", node.port)),
+    };
+
+    // hysteria2 / tuic 运行在 QUIC 之上，TCP 端口不通并不代表节点不可用
+    if let Some(node_protocol) = Protocol::parse(&node.protocol).filter(Protocol::is_udp) {
+        return CheckOutcome::unchecked(format!("{} 基于 UDP，Worker 无法检测，已跳过", node_protocol));
+    }
+
+    // https 代理本身运行在 TLS 之上，由 runtime 完成 TLS 建连
+    let secure = node.protocol == "https";
+    let start_time = js_sys::Date::now();
+
+    let mut socket = match with_timeout(tcp_connect(host, port, secure), CONNECT_TIMEOUT_MS).await {
+        Some(Ok(socket)) => socket,
+        Some(Err(e)) => return CheckOutcome::invalid(format!("TCP 连接失败: {}", e)),
+        None => return CheckOutcome::invalid(format!("TCP 连接超时 ({}ms)", CONNECT_TIMEOUT_MS)),
+    };
+    let latency = (js_sys::Date::now() - start_time) as i64;
+
+    let probe = probe_for(node);
+    let (stage, result) = match &probe {
+        Probe::TcpOnly => ("TCP", Ok(())),
+        Probe::HttpConnect => ("HTTP CONNECT", run_probe(http_connect_probe(&mut socket, node)).await),
+        Probe::Socks5 => ("SOCKS5 握手", run_probe(socks5_probe(&mut socket, node)).await),
+        Probe::Tls(params) => ("TLS 握手", run_probe(tls_probe(&mut socket, params)).await),
+    };
+    let _ = socket.close().await;
+
+    match result {
+        Ok(()) if matches!(probe, Probe::TcpOnly) => CheckOutcome::valid(latency, "节点可用 (仅验证 TCP 连通)".to_string()),
+        Ok(()) => CheckOutcome::valid(latency, format!("节点可用 ({} 成功)", stage)),
+        Err(e) => CheckOutcome::invalid(format!("{} 失败: {}", stage, e)),
+    }
+}
+
+// 批量检测结果，skipped 为超出时间预算未完成检测的节点 id
+pub struct BatchCheckOutcome {
+    pub results: Vec<ProxyCheckResult>,
+    pub skipped: Vec<i64>,
+}
+
+// 以有界并发检测一组节点，超出 budget_ms 后放弃未完成的检测
+pub async fn check_proxy_nodes(nodes: Vec<ProxyNode>, budget_ms: f64) -> BatchCheckOutcome {
+    let deadline = js_sys::Date::now() + budget_ms;
+    let ids: Vec<i64> = nodes.iter().map(|n| n.id).collect();
+
+    let mut pending = stream::iter(nodes)
+        .map(|node| async move {
+            let outcome = check_proxy_node(&node).await;
+            ProxyCheckResult {
+                id: node.id,
+                status: outcome.status.to_string(),
+                latency: outcome.latency,
+                message: outcome.message,
+            }
+        })
+        .buffer_unordered(CHECK_CONCURRENCY);
+
+    let mut results: Vec<ProxyCheckResult> = Vec::with_capacity(ids.len());
+    loop {
+        let remaining = deadline - js_sys::Date::now();
+        if remaining <= 0.0 {
+            break;
+        }
+        match with_timeout(pending.next(), remaining as u64).await {
+            Some(Some(result)) => results.push(result),
+            // 全部完成或预算耗尽
+            Some(None) | None => break,
+        }
+    }
+
+    let done: HashSet<i64> = results.iter().map(|r| r.id).collect();
+    let skipped = ids.into_iter().filter(|id| !done.contains(id)).collect();
+
+    BatchCheckOutcome { results, skipped }
+}
+
+// 建立 TCP 连接并等待握手完成
+async fn tcp_connect(host: &str, port: u16, secure: bool) -> Result<Socket> {
+    let transport = if secure { SecureTransport::On } else { SecureTransport::Off };
+    let socket = ConnectionBuilder::new()
+        .secure_transport(transport)
+        .connect(host, port)?;
+    socket.opened().await?;
+    Ok(socket)
+}
+
+// 为握手探测加上超时
+async fn run_probe<F>(probe: F) -> std::result::Result<(), String>
+where
+    F: Future<Output = std::result::Result<(), String>>,
+{
+    match with_timeout(probe, HANDSHAKE_TIMEOUT_MS).await {
+        Some(result) => result,
+        None => Err(format!("超时 ({}ms)", HANDSHAKE_TIMEOUT_MS)),
+    }
+}
+
+// 为 future 加上超时，超时返回 None
+pub async fn with_timeout<F: Future>(fut: F, timeout_ms: u64) -> Option<F::Output> {
+    let fut = std::pin::pin!(fut);
+    let delay = std::pin::pin!(Delay::from(Duration::from_millis(timeout_ms)));
+
+    match select(fut, delay).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+// 根据协议选择探测方式
+fn probe_for(node: &ProxyNode) -> Probe {
+    let (protocol, config) = match protocol::node_config(node) {
+        Ok(v) => v,
+        Err(_) => return Probe::TcpOnly,
+    };
+
+    match protocol {
+        Protocol::Http | Protocol::Https => Probe::HttpConnect,
+        Protocol::Socks5 => Probe::Socks5,
+        // REALITY 对未认证的客户端转发到伪装站点，同样可以用 TLS 握手探测
+        Protocol::Trojan | Protocol::Vmess | Protocol::Vless => match tls_params(node, config) {
+            Some(params) => Probe::Tls(params),
+            None => Probe::TcpOnly,
+        },
+        Protocol::Ss | Protocol::Ssr | Protocol::Hysteria2 | Protocol::Tuic => Probe::TcpOnly,
+    }
+}
+
+// 从协议配置中读取 TLS 参数，未启用 TLS 时返回 None；未设置 sni 时依次使用传输层的 host 与节点地址
+fn tls_params(node: &ProxyNode, config: NodeConfig) -> Option<TlsParams> {
+    let tls = config.tls_options().filter(|tls| tls.enabled)?;
+    let host = config.transport().and_then(|transport| transport.host);
+
+    Some(TlsParams {
+        sni: tls.sni.or(host).unwrap_or_else(|| node.address.trim().to_string()),
+        alpn: tls.alpn,
+    })
+}
+
+// HTTP 代理：发送 CONNECT 请求，带上 Basic 认证
+async fn http_connect_probe(socket: &mut Socket, node: &ProxyNode) -> std::result::Result<(), String> {
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", PROBE_TARGET);
+    if let Some(username) = node.username.as_deref().filter(|u| !u.is_empty()) {
+        let credentials = format!("{}:{}", username, node.password.as_deref().unwrap_or(""));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", STANDARD.encode(credentials)));
+    }
+    request.push_str("\r\n");
+
+    socket.write_all(request.as_bytes()).await.map_err(|e| format!("发送请求失败: {}", e))?;
+    socket.flush().await.map_err(|e| format!("发送请求失败: {}", e))?;
+
+    // 读取到响应头结束
+    let mut response: Vec<u8> = Vec::new();
+    let mut buf = [0u8; 512];
+    while !response.windows(4).any(|w| w == b"\r\n\r\n") && response.len() < 8192 {
+        let n = socket.read(&mut buf).await.map_err(|e| format!("读取响应失败: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        response.extend_from_slice(&buf[..n]);
+    }
+
+    let text = String::from_utf8_lossy(&response);
+    let status_line = text.lines().next().unwrap_or("");
+    if !status_line.starts_with("HTTP/") {
+        return Err("响应不是 HTTP 协议".to_string());
+    }
+
+    let code: u16 = status_line.split_whitespace().nth(1)
+        .and_then(|c| c.parse().ok())
+        .unwrap_or(0);
+    match code {
+        200..=299 => Ok(()),
+        407 => Err("代理认证失败 (HTTP 407)".to_string()),
+        _ => Err(format!("代理返回 {}", status_line.trim())),
+    }
+}
+
+// SOCKS5：发送问候并在需要时完成用户名/密码子协商 (RFC 1929)
+async fn socks5_probe(socket: &mut Socket, node: &ProxyNode) -> std::result::Result<(), String> {
+    let username = node.username.as_deref().unwrap_or("");
+    let password = node.password.as_deref().unwrap_or("");
+    let has_auth = !username.is_empty();
+
+    let greeting: &[u8] = if has_auth { &[0x05, 0x02, 0x00, 0x02] } else { &[0x05, 0x01, 0x00] };
+    socket.write_all(greeting).await.map_err(|e| format!("发送问候失败: {}", e))?;
+
+    let mut reply = [0u8; 2];
+    socket.read_exact(&mut reply).await.map_err(|e| format!("读取问候响应失败: {}", e))?;
+    if reply[0] != 0x05 {
+        return Err(format!("不是 SOCKS5 服务 (版本号 {})", reply[0]));
+    }
+
+    match reply[1] {
+        0x00 => Ok(()),
+        0x02 if has_auth => {
+            if username.len() > 255 || password.len() > 255 {
+                return Err("用户名或密码过长".to_string());
+            }
+            let mut auth = vec![0x01, username.len() as u8];
+            auth.extend_from_slice(username.as_bytes());
+            auth.push(password.len() as u8);
+            auth.extend_from_slice(password.as_bytes());
+            socket.write_all(&auth).await.map_err(|e| format!("发送认证失败: {}", e))?;
+
+            let mut status = [0u8; 2];
+            socket.read_exact(&mut status).await.map_err(|e| format!("读取认证响应失败: {}", e))?;
+            if status[1] == 0x00 {
+                Ok(())
+            } else {
+                Err("用户名/密码认证失败".to_string())
+            }
+        }
+        0x02 => Err("服务器要求用户名/密码认证，但节点未配置".to_string()),
+        0xFF => Err("服务器不接受任何认证方式".to_string()),
+        method => Err(format!("不支持的认证方式 0x{:02x}", method)),
+    }
+}
+
+// TLS：在明文 TCP 上发送携带配置 SNI 的 ClientHello，等待 ServerHello。
+// Workers 的 startTls 无法指定 SNI，因此手动完成握手的第一轮往返
+async fn tls_probe(socket: &mut Socket, params: &TlsParams) -> std::result::Result<(), String> {
+    let hello = build_client_hello(&params.sni, &params.alpn);
+    socket.write_all(&hello).await.map_err(|e| format!("发送 ClientHello 失败: {}", e))?;
+    socket.flush().await.map_err(|e| format!("发送 ClientHello 失败: {}", e))?;
+
+    let mut header = [0u8; 5];
+    socket.read_exact(&mut header).await
+        .map_err(|_| format!("服务器关闭了连接 (SNI: {})", params.sni))?;
+
+    match header[0] {
+        // handshake 记录，首条消息应为 ServerHello
+        0x16 => {
+            let mut msg_type = [0u8; 1];
+            socket.read_exact(&mut msg_type).await.map_err(|e| format!("读取 ServerHello 失败: {}", e))?;
+            if msg_type[0] == 0x02 {
+                Ok(())
+            } else {
+                Err(format!("意外的握手消息类型 {}", msg_type[0]))
+            }
+        }
+        // alert 记录
+        0x15 => {
+            let mut alert = [0u8; 2];
+            socket.read_exact(&mut alert).await.map_err(|e| format!("读取 alert 失败: {}", e))?;
+            Err(format!("服务器返回 alert: {} (SNI: {})", tls_alert_name(alert[1]), params.sni))
+        }
+        _ => Err("响应不是 TLS 协议".to_string()),
+    }
+}
+
+fn tls_alert_name(code: u8) -> String {
+    match code {
+        40 => "handshake_failure".to_string(),
+        70 => "protocol_version".to_string(),
+        80 => "internal_error".to_string(),
+        112 => "unrecognized_name".to_string(),
+        120 => "no_application_protocol".to_string(),
+        other => format!("code {}", other),
+    }
+}
+
+// 构造 TLS 1.2/1.3 兼容的 ClientHello 记录
+fn build_client_hello(sni: &str, alpn: &[String]) -> Vec<u8> {
+    let mut extensions: Vec<u8> = Vec::new();
+
+    // server_name
+    let host = sni.as_bytes();
+    let mut server_name = Vec::new();
+    push_u16(&mut server_name, (host.len() + 3) as u16);
+    server_name.push(0x00);
+    push_u16(&mut server_name, host.len() as u16);
+    server_name.extend_from_slice(host);
+    push_extension(&mut extensions, 0x0000, &server_name);
+
+    // supported_groups: x25519, secp256r1, secp384r1
+    push_extension(&mut extensions, 0x000a, &[0x00, 0x06, 0x00, 0x1d, 0x00, 0x17, 0x00, 0x18]);
+    // ec_point_formats: uncompressed
+    push_extension(&mut extensions, 0x000b, &[0x01, 0x00]);
+    // signature_algorithms
+    push_extension(&mut extensions, 0x000d, &[
+        0x00, 0x10,
+        0x04, 0x03, 0x08, 0x04, 0x04, 0x01, 0x05, 0x03,
+        0x08, 0x05, 0x05, 0x01, 0x08, 0x06, 0x06, 0x01,
+    ]);
+
+    // application_layer_protocol_negotiation
+    if !alpn.is_empty() {
+        let mut list = Vec::new();
+        for proto in alpn.iter().filter(|p| p.len() < 256) {
+            list.push(proto.len() as u8);
+            list.extend_from_slice(proto.as_bytes());
+        }
+        let mut data = Vec::new();
+        push_u16(&mut data, list.len() as u16);
+        data.extend_from_slice(&list);
+        push_extension(&mut extensions, 0x0010, &data);
+    }
+
+    // supported_versions: TLS 1.3, TLS 1.2
+    push_extension(&mut extensions, 0x002b, &[0x04, 0x03, 0x04, 0x03, 0x03]);
+    // psk_key_exchange_modes: psk_dhe_ke
+    push_extension(&mut extensions, 0x002d, &[0x01, 0x01]);
+
+    // key_share: 任意 32 字节都是合法的 x25519 公钥，服务端在 ServerHello 之前不需要私钥
+    let mut key_share = Vec::new();
+    push_u16(&mut key_share, 36);
+    push_u16(&mut key_share, 0x001d);
+    push_u16(&mut key_share, 32);
+    key_share.extend_from_slice(&random_bytes());
+    push_extension(&mut extensions, 0x0033, &key_share);
+
+    let mut hello = Vec::new();
+    hello.extend_from_slice(&[0x03, 0x03]);
+    hello.extend_from_slice(&random_bytes());
+    // legacy_session_id（TLS 1.3 兼容模式）
+    hello.push(32);
+    hello.extend_from_slice(&random_bytes());
+    // cipher_suites
+    let suites: [u16; 9] = [0x1301, 0x1302, 0x1303, 0xc02b, 0xc02f, 0xc02c, 0xc030, 0xcca9, 0xcca8];
+    push_u16(&mut hello, (suites.len() * 2) as u16);
+    for suite in suites {
+        push_u16(&mut hello, suite);
+    }
+    // compression_methods: null
+    hello.extend_from_slice(&[0x01, 0x00]);
+    push_u16(&mut hello, extensions.len() as u16);
+    hello.extend_from_slice(&extensions);
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(hello.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&hello);
+
+    let mut record = vec![0x16, 0x03, 0x01];
+    push_u16(&mut record, handshake.len() as u16);
+    record.extend_from_slice(&handshake);
+    record
+}
+
+fn push_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_extension(buf: &mut Vec<u8>, ext_type: u16, data: &[u8]) {
+    push_u16(buf, ext_type);
+    push_u16(buf, data.len() as u16);
+    buf.extend_from_slice(data);
+}
+
+fn random_bytes() -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+    bytes[16..].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+    bytes
+}
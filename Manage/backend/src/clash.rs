@@ -0,0 +1,555 @@
+// Clash.Meta（mihomo）配置：按协议输出 proxies 并为每个节点分组生成一个 select 代理组，
+// 以及导入 Clash 订阅中的 proxies 列表
+
+use serde_yaml::{Mapping, Value};
+use crate::export::{ProxyLayout, UniqueNames, AUTO_GROUP, SELECT_GROUP, URL_TEST_INTERVAL, URL_TEST_URL};
+use crate::models::{NodeGroup, ProxyNode};
+use crate::protocol::{self, Hysteria2Config, NodeConfig, Protocol, SsConfig, SsrConfig, TlsOptions, Transport, TrojanConfig, TuicConfig, VlessConfig, VmessConfig};
+use crate::share_link::{ParsedNode, ParsedSubscription, UnsupportedEntry};
+
+// 生成完整配置，返回 YAML 文本、无法表示而跳过的节点 id，
+// 以及其中因 ss 插件不受支持而跳过的节点（注明插件名）
+pub fn render(nodes: &[ProxyNode], groups: &[NodeGroup]) -> Result<(String, Vec<i64>, Vec<UnsupportedEntry>), String> {
+    let mut names = UniqueNames::new(&["DIRECT", "REJECT", SELECT_GROUP, AUTO_GROUP]);
+    let mut proxies: Vec<Value> = Vec::new();
+    let mut skipped = Vec::new();
+    let mut unsupported = Vec::new();
+    let mut layout = ProxyLayout::default();
+
+    for node in nodes {
+        let name = names.claim(&node.name);
+        match proxy_entry(node, &name) {
+            Ok(proxy) => {
+                proxies.push(Value::Mapping(proxy));
+                layout.add(node.group_name.as_deref(), name);
+            }
+            Err(_) => {
+                names.release(&name);
+                skipped.push(node.id);
+                if let Some(plugin) = unsupported_ss_plugin(node) {
+                    unsupported.push(UnsupportedEntry { name: node.name.clone(), kind: ss_plugin_kind(&plugin) });
+                }
+            }
+        }
+    }
+    let layout = layout.finish(groups, &mut names);
+
+    let mut proxy_groups = vec![Value::Mapping(proxy_group(SELECT_GROUP, "select", layout.selector_members("DIRECT")))];
+    if !layout.all.is_empty() {
+        let mut auto = proxy_group(AUTO_GROUP, "url-test", layout.all.clone());
+        set(&mut auto, "url", URL_TEST_URL);
+        set(&mut auto, "interval", URL_TEST_INTERVAL);
+        proxy_groups.push(Value::Mapping(auto));
+    }
+    for (group_name, list) in &layout.groups {
+        proxy_groups.push(Value::Mapping(proxy_group(group_name, "select", list.clone())));
+    }
+
+    let mut config = Mapping::new();
+    set(&mut config, "proxies", Value::Sequence(proxies));
+    set(&mut config, "proxy-groups", Value::Sequence(proxy_groups));
+    set(&mut config, "rules", vec![format!("MATCH,{}", SELECT_GROUP)]);
+
+    let content = serde_yaml::to_string(&config).map_err(|e| format!("生成 Clash 配置失败: {}", e))?;
+    Ok((content, skipped, unsupported))
+}
+
+fn proxy_group(name: &str, kind: &str, proxies: Vec<String>) -> Mapping {
+    let mut group = Mapping::new();
+    set(&mut group, "name", name);
+    set(&mut group, "type", kind);
+    set(&mut group, "proxies", proxies);
+    group
+}
+
+// 单个节点的 proxies 条目，目标客户端无法表示时返回 Err
+fn proxy_entry(node: &ProxyNode, name: &str) -> Result<Mapping, String> {
+    let (node_protocol, config) = protocol::node_config(node)?;
+    let username = node.username.as_deref().filter(|u| !u.is_empty());
+    let password = node.password.as_deref().filter(|p| !p.is_empty());
+
+    let mut proxy = Mapping::new();
+    set(&mut proxy, "name", name);
+
+    match (node_protocol, &config) {
+        (Protocol::Http | Protocol::Https | Protocol::Socks5, _) => {
+            set(&mut proxy, "type", if node_protocol == Protocol::Socks5 { "socks5" } else { "http" });
+            set_server(&mut proxy, node);
+            if let Some(username) = username {
+                set(&mut proxy, "username", username);
+            }
+            if let Some(password) = password {
+                set(&mut proxy, "password", password);
+            }
+            if node_protocol == Protocol::Https {
+                set(&mut proxy, "tls", true);
+            }
+        }
+        (Protocol::Ss, NodeConfig::Ss(c)) => {
+            set(&mut proxy, "type", "ss");
+            set_server(&mut proxy, node);
+            set(&mut proxy, "cipher", c.cipher.as_str());
+            set(&mut proxy, "password", password.unwrap_or(""));
+            set(&mut proxy, "udp", true);
+            apply_ss_plugin(&mut proxy, c)?;
+        }
+        (Protocol::Ssr, NodeConfig::Ssr(c)) => {
+            set(&mut proxy, "type", "ssr");
+            set_server(&mut proxy, node);
+            set(&mut proxy, "cipher", c.cipher.as_str());
+            set(&mut proxy, "password", password.unwrap_or(""));
+            set(&mut proxy, "protocol", c.protocol.as_str());
+            if let Some(param) = &c.protocol_param {
+                set(&mut proxy, "protocol-param", param.as_str());
+            }
+            set(&mut proxy, "obfs", c.obfs.as_str());
+            if let Some(param) = &c.obfs_param {
+                set(&mut proxy, "obfs-param", param.as_str());
+            }
+            set(&mut proxy, "udp", true);
+        }
+        (Protocol::Vmess, NodeConfig::Vmess(c)) => {
+            set(&mut proxy, "type", "vmess");
+            set_server(&mut proxy, node);
+            set(&mut proxy, "uuid", c.uuid.as_str());
+            set(&mut proxy, "alterId", c.alter_id);
+            set(&mut proxy, "cipher", c.security.as_str());
+            set(&mut proxy, "udp", true);
+            let tls = c.tls_options();
+            if tls.enabled {
+                set(&mut proxy, "tls", true);
+                apply_tls(&mut proxy, &tls, "servername");
+            }
+            apply_transport(&mut proxy, &c.transport())?;
+        }
+        (Protocol::Vless, NodeConfig::Vless(c)) => {
+            set(&mut proxy, "type", "vless");
+            set_server(&mut proxy, node);
+            set(&mut proxy, "uuid", c.uuid.as_str());
+            if let Some(flow) = c.flow.as_deref().filter(|f| !f.is_empty()) {
+                set(&mut proxy, "flow", flow);
+            }
+            set(&mut proxy, "udp", true);
+            let tls = c.tls_options();
+            if tls.enabled {
+                set(&mut proxy, "tls", true);
+                apply_tls(&mut proxy, &tls, "servername");
+            }
+            if c.is_reality() {
+                let mut opts = Mapping::new();
+                set(&mut opts, "public-key", c.public_key.as_deref().unwrap_or(""));
+                if let Some(short_id) = c.short_id.as_deref().filter(|s| !s.is_empty()) {
+                    set(&mut opts, "short-id", short_id);
+                }
+                set(&mut proxy, "reality-opts", opts);
+            }
+            apply_transport(&mut proxy, &c.transport())?;
+        }
+        (Protocol::Trojan, NodeConfig::Trojan(c)) => {
+            set(&mut proxy, "type", "trojan");
+            set_server(&mut proxy, node);
+            set(&mut proxy, "password", password.unwrap_or(""));
+            set(&mut proxy, "udp", true);
+            apply_tls(&mut proxy, &c.tls_options(), "sni");
+            apply_transport(&mut proxy, &c.transport())?;
+        }
+        (Protocol::Hysteria2, NodeConfig::Hysteria2(c)) => {
+            set(&mut proxy, "type", "hysteria2");
+            set_server(&mut proxy, node);
+            set(&mut proxy, "password", password.unwrap_or(""));
+            if let Some(up) = c.up.as_deref().filter(|u| !u.is_empty()) {
+                set(&mut proxy, "up", up);
+            }
+            if let Some(down) = c.down.as_deref().filter(|d| !d.is_empty()) {
+                set(&mut proxy, "down", down);
+            }
+            if let Some(obfs) = c.obfs.as_deref().filter(|o| !o.is_empty()) {
+                set(&mut proxy, "obfs", obfs);
+                set(&mut proxy, "obfs-password", c.obfs_password.as_deref().unwrap_or(""));
+            }
+            apply_tls(&mut proxy, &c.tls_options(), "sni");
+        }
+        (Protocol::Tuic, NodeConfig::Tuic(c)) => {
+            set(&mut proxy, "type", "tuic");
+            set_server(&mut proxy, node);
+            set(&mut proxy, "uuid", c.uuid.as_str());
+            set(&mut proxy, "password", password.unwrap_or(""));
+            if let Some(congestion_control) = c.congestion_control.as_deref().filter(|v| !v.is_empty()) {
+                set(&mut proxy, "congestion-controller", congestion_control);
+            }
+            if let Some(udp_relay_mode) = c.udp_relay_mode.as_deref().filter(|v| !v.is_empty()) {
+                set(&mut proxy, "udp-relay-mode", udp_relay_mode);
+            }
+            apply_tls(&mut proxy, &c.tls_options(), "sni");
+        }
+        _ => return Err(format!("{} 节点配置与协议不符", node_protocol)),
+    }
+
+    Ok(proxy)
+}
+
+fn set_server(proxy: &mut Mapping, node: &ProxyNode) {
+    // IPv6 地址不带方括号
+    set(proxy, "server", node.address.trim_start_matches('[').trim_end_matches(']'));
+    set(proxy, "port", node.port);
+}
+
+fn apply_tls(proxy: &mut Mapping, tls: &TlsOptions, sni_key: &str) {
+    if let Some(sni) = &tls.sni {
+        set(proxy, sni_key, sni.as_str());
+    }
+    if !tls.alpn.is_empty() {
+        set(proxy, "alpn", tls.alpn.clone());
+    }
+    if tls.allow_insecure {
+        set(proxy, "skip-cert-verify", true);
+    }
+    if let Some(fingerprint) = &tls.fingerprint {
+        set(proxy, "client-fingerprint", fingerprint.as_str());
+    }
+}
+
+fn apply_transport(proxy: &mut Mapping, transport: &Transport) -> Result<(), String> {
+    let host_headers = |host: &Option<String>| -> Option<Mapping> {
+        let mut headers = Mapping::new();
+        set(&mut headers, "Host", host.as_deref()?);
+        Some(headers)
+    };
+
+    match transport.network.as_str() {
+        "" | "tcp" => {
+            // tcp + http 伪装对应 Clash 的 http 传输
+            if transport.header_type.as_deref() == Some("http") {
+                let mut opts = Mapping::new();
+                set(&mut opts, "path", vec![transport.path.clone().unwrap_or_else(|| "/".to_string())]);
+                if let Some(host) = &transport.host {
+                    let mut headers = Mapping::new();
+                    set(&mut headers, "Host", vec![host.clone()]);
+                    set(&mut opts, "headers", headers);
+                }
+                set(proxy, "network", "http");
+                set(proxy, "http-opts", opts);
+            }
+        }
+        "ws" | "httpupgrade" => {
+            let mut opts = Mapping::new();
+            if let Some(path) = &transport.path {
+                set(&mut opts, "path", path.as_str());
+            }
+            if let Some(headers) = host_headers(&transport.host) {
+                set(&mut opts, "headers", headers);
+            }
+            if transport.network == "httpupgrade" {
+                set(&mut opts, "v2ray-http-upgrade", true);
+            }
+            set(proxy, "network", "ws");
+            set(proxy, "ws-opts", opts);
+        }
+        "h2" | "http" => {
+            let mut opts = Mapping::new();
+            if let Some(host) = &transport.host {
+                set(&mut opts, "host", vec![host.clone()]);
+            }
+            if let Some(path) = &transport.path {
+                set(&mut opts, "path", path.as_str());
+            }
+            set(proxy, "network", "h2");
+            set(proxy, "h2-opts", opts);
+        }
+        "grpc" => {
+            let mut opts = Mapping::new();
+            if let Some(service_name) = &transport.service_name {
+                set(&mut opts, "grpc-service-name", service_name.as_str());
+            }
+            set(proxy, "network", "grpc");
+            set(proxy, "grpc-opts", opts);
+        }
+        other => return Err(format!("Clash 不支持 {} 传输", other)),
+    }
+    Ok(())
+}
+
+// Clash 支持的 SIP003 插件，与 apply_ss_plugin 一致
+fn is_supported_ss_plugin(name: &str) -> bool {
+    matches!(name, "obfs-local" | "simple-obfs" | "obfs" | "v2ray-plugin")
+}
+
+// 节点使用了 Clash 不支持的 ss 插件时返回插件名
+fn unsupported_ss_plugin(node: &ProxyNode) -> Option<String> {
+    match protocol::node_config(node).ok()?.1 {
+        NodeConfig::Ss(c) => c.plugin_options().map(|(name, _)| name).filter(|name| !is_supported_ss_plugin(name)),
+        _ => None,
+    }
+}
+
+// unsupported 中 ss 插件不受支持的条目类型
+fn ss_plugin_kind(plugin: &str) -> String {
+    format!("ss 插件 {}", plugin)
+}
+
+// SIP003 插件转换为 Clash 的 plugin / plugin-opts
+fn apply_ss_plugin(proxy: &mut Mapping, config: &SsConfig) -> Result<(), String> {
+    let Some((name, options)) = config.plugin_options() else {
+        return Ok(());
+    };
+    let mut opts = Mapping::new();
+    match name.as_str() {
+        "obfs-local" | "simple-obfs" | "obfs" => {
+            set(&mut opts, "mode", options.get("obfs").map(String::as_str).unwrap_or("http"));
+            if let Some(host) = options.get("obfs-host") {
+                set(&mut opts, "host", host.as_str());
+            }
+            set(proxy, "plugin", "obfs");
+        }
+        "v2ray-plugin" => {
+            set(&mut opts, "mode", options.get("mode").map(String::as_str).unwrap_or("websocket"));
+            if options.contains_key("tls") {
+                set(&mut opts, "tls", true);
+            }
+            if let Some(host) = options.get("host") {
+                set(&mut opts, "host", host.as_str());
+            }
+            if let Some(path) = options.get("path") {
+                set(&mut opts, "path", path.as_str());
+            }
+            set(proxy, "plugin", "v2ray-plugin");
+        }
+        other => return Err(format!("Clash 不支持 ss 插件 {}", other)),
+    }
+    set(proxy, "plugin-opts", opts);
+    Ok(())
+}
+
+fn set(map: &mut Mapping, key: &str, value: impl Into<Value>) {
+    map.insert(Value::String(key.to_string()), value.into());
+}
+
+// 解析 Clash 配置或 proxy-provider 中的 proxies 列表，内容不是 Clash YAML 时返回 None；
+// 不支持的类型与 ss 插件记入 unsupported，配置无效的条目直接忽略
+pub fn parse_proxies(content: &str) -> Option<ParsedSubscription> {
+    let config: Value = serde_yaml::from_str(content).ok()?;
+    let proxies = config.get("proxies")?.as_sequence()?;
+
+    let mut parsed = ParsedSubscription::default();
+    for proxy in proxies.iter().filter_map(Value::as_mapping) {
+        let kind = get_str(proxy, "type").unwrap_or_default().to_lowercase();
+        match parse_proxy(proxy, &kind) {
+            Some(Ok(node)) => parsed.nodes.push(node),
+            Some(Err(_)) => {}
+            None => parsed.unsupported.push(UnsupportedEntry {
+                name: get_str(proxy, "name").unwrap_or_default(),
+                kind: unsupported_kind(proxy, kind),
+            }),
+        }
+    }
+    Some(parsed)
+}
+
+// 不支持的条目类型；ss 因插件无法表示而不支持时注明插件名
+fn unsupported_kind(proxy: &Mapping, kind: String) -> String {
+    match get_str(proxy, "plugin") {
+        Some(plugin) if kind == "ss" => ss_plugin_kind(&plugin),
+        _ => kind,
+    }
+}
+
+// 类型不支持时返回 None
+fn parse_proxy(proxy: &Mapping, kind: &str) -> Option<Result<ParsedNode, String>> {
+    let node_protocol = match kind {
+        "http" if get_bool(proxy, "tls") => Protocol::Https,
+        "http" => Protocol::Http,
+        // socks5 over TLS 无法用现有协议表示
+        "socks5" if !get_bool(proxy, "tls") => Protocol::Socks5,
+        "ss" => Protocol::Ss,
+        "ssr" => Protocol::Ssr,
+        "vmess" => Protocol::Vmess,
+        "vless" => Protocol::Vless,
+        "trojan" => Protocol::Trojan,
+        "hysteria2" => Protocol::Hysteria2,
+        // 只有 TUIC v5 使用 uuid，v4 的 token 认证无法表示
+        "tuic" if proxy.contains_key("uuid") => Protocol::Tuic,
+        _ => return None,
+    };
+    // ss 插件无法表示时同样视为不支持
+    let config = match node_protocol {
+        Protocol::Ss => NodeConfig::Ss(SsConfig {
+            cipher: get_str(proxy, "cipher").unwrap_or_default(),
+            plugin: ss_plugin_from_clash(proxy)?,
+        }),
+        Protocol::Ssr => NodeConfig::Ssr(SsrConfig {
+            cipher: get_str(proxy, "cipher").unwrap_or_default(),
+            protocol: get_str(proxy, "protocol").unwrap_or_default(),
+            protocol_param: get_str(proxy, "protocol-param"),
+            obfs: get_str(proxy, "obfs").unwrap_or_default(),
+            obfs_param: get_str(proxy, "obfs-param"),
+        }),
+        Protocol::Vmess => NodeConfig::Vmess(VmessConfig::from_parts(
+            get_str(proxy, "uuid").unwrap_or_default(),
+            get_str(proxy, "alterId").and_then(|a| a.parse().ok()).unwrap_or(0),
+            get_str(proxy, "cipher").unwrap_or_default(),
+            clash_transport(proxy),
+            clash_tls(proxy, "servername", get_bool(proxy, "tls")),
+        )),
+        Protocol::Vless => {
+            let reality = proxy.get("reality-opts").and_then(Value::as_mapping);
+            let mut config = VlessConfig {
+                uuid: get_str(proxy, "uuid").unwrap_or_default(),
+                flow: get_str(proxy, "flow"),
+                public_key: reality.and_then(|r| get_str(r, "public-key")),
+                short_id: reality.and_then(|r| get_str(r, "short-id")),
+                security: if reality.is_some() { "reality" } else { "none" }.to_string(),
+                ..Default::default()
+            };
+            config.set_parts(clash_transport(proxy), clash_tls(proxy, "servername", get_bool(proxy, "tls")));
+            NodeConfig::Vless(config)
+        }
+        Protocol::Trojan => NodeConfig::Trojan(TrojanConfig::from_parts(clash_transport(proxy), clash_tls(proxy, "sni", true))),
+        Protocol::Hysteria2 => {
+            let tls = clash_tls(proxy, "sni", true);
+            NodeConfig::Hysteria2(Hysteria2Config {
+                sni: tls.sni,
+                alpn: tls.alpn,
+                allow_insecure: tls.allow_insecure,
+                obfs: get_str(proxy, "obfs"),
+                obfs_password: get_str(proxy, "obfs-password"),
+                up: get_str(proxy, "up"),
+                down: get_str(proxy, "down"),
+                ..Default::default()
+            })
+        }
+        Protocol::Tuic => {
+            let tls = clash_tls(proxy, "sni", true);
+            NodeConfig::Tuic(TuicConfig {
+                uuid: get_str(proxy, "uuid").unwrap_or_default(),
+                sni: tls.sni,
+                alpn: tls.alpn,
+                allow_insecure: tls.allow_insecure,
+                congestion_control: get_str(proxy, "congestion-controller"),
+                udp_relay_mode: get_str(proxy, "udp-relay-mode"),
+                ..Default::default()
+            })
+        }
+        _ => NodeConfig::Plain,
+    };
+
+    Some(ParsedNode::build(
+        get_str(proxy, "name").unwrap_or_default(),
+        node_protocol,
+        get_str(proxy, "server").unwrap_or_default(),
+        get_str(proxy, "port").and_then(|p| p.parse().ok()).unwrap_or(0),
+        get_str(proxy, "username"),
+        get_str(proxy, "password"),
+        config,
+    ))
+}
+
+fn clash_tls(proxy: &Mapping, sni_key: &str, enabled: bool) -> TlsOptions {
+    TlsOptions {
+        enabled,
+        sni: get_str(proxy, sni_key).or_else(|| get_str(proxy, "servername")),
+        alpn: get_list(proxy, "alpn").unwrap_or_default(),
+        allow_insecure: get_bool(proxy, "skip-cert-verify"),
+        fingerprint: get_str(proxy, "client-fingerprint"),
+    }
+}
+
+// 读取 network 与对应的 ws-opts / h2-opts / http-opts / grpc-opts
+fn clash_transport(proxy: &Mapping) -> Transport {
+    let network = get_str(proxy, "network").unwrap_or_else(|| "tcp".to_string());
+    let opts = proxy.get(format!("{}-opts", network).as_str()).and_then(Value::as_mapping);
+    let mut transport = Transport { network, ..Default::default() };
+    // Clash 的 http 传输即 tcp + http 伪装
+    if transport.network == "http" {
+        transport.network = "tcp".to_string();
+        transport.header_type = Some("http".to_string());
+    }
+    let Some(opts) = opts else {
+        return transport;
+    };
+
+    match transport.network.as_str() {
+        "ws" => {
+            transport.path = get_str(opts, "path");
+            transport.host = opts.get("headers").and_then(Value::as_mapping).and_then(|h| get_str(h, "Host"));
+            if get_bool(opts, "v2ray-http-upgrade") {
+                transport.network = "httpupgrade".to_string();
+            }
+        }
+        "h2" => {
+            transport.path = get_str(opts, "path");
+            transport.host = get_list(opts, "host").and_then(|hosts| hosts.into_iter().next());
+        }
+        "tcp" => {
+            transport.path = get_list(opts, "path").and_then(|paths| paths.into_iter().next());
+            transport.host = opts
+                .get("headers")
+                .and_then(Value::as_mapping)
+                .and_then(|h| get_list(h, "Host"))
+                .and_then(|hosts| hosts.into_iter().next());
+        }
+        "grpc" => transport.service_name = get_str(opts, "grpc-service-name"),
+        _ => {}
+    }
+    transport
+}
+
+// 转换为 SIP003 插件字符串；无法表示的插件返回 None，该节点记入 unsupported
+fn ss_plugin_from_clash(proxy: &Mapping) -> Option<Option<String>> {
+    let Some(plugin) = get_str(proxy, "plugin") else {
+        return Some(None);
+    };
+    let opts = proxy.get("plugin-opts").and_then(Value::as_mapping);
+    let opt = |key: &str| opts.and_then(|o| get_str(o, key));
+
+    let mut parts = Vec::new();
+    match plugin.as_str() {
+        "obfs" => {
+            parts.push("obfs-local".to_string());
+            parts.push(format!("obfs={}", opt("mode").unwrap_or_else(|| "http".to_string())));
+            if let Some(host) = opt("host") {
+                parts.push(format!("obfs-host={}", host));
+            }
+        }
+        "v2ray-plugin" => {
+            parts.push("v2ray-plugin".to_string());
+            parts.push(format!("mode={}", opt("mode").unwrap_or_else(|| "websocket".to_string())));
+            if opts.is_some_and(|o| get_bool(o, "tls")) {
+                parts.push("tls".to_string());
+            }
+            if let Some(host) = opt("host") {
+                parts.push(format!("host={}", host));
+            }
+            if let Some(path) = opt("path") {
+                parts.push(format!("path={}", path));
+            }
+        }
+        _ => return None,
+    }
+    Some(Some(parts.join(";")))
+}
+
+fn yaml_str(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn get_str(map: &Mapping, key: &str) -> Option<String> {
+    map.get(key).and_then(yaml_str)
+}
+
+fn get_bool(map: &Mapping, key: &str) -> bool {
+    get_str(map, key).is_some_and(|v| v == "true" || v == "1")
+}
+
+// 兼容单个字符串与字符串列表两种写法
+fn get_list(map: &Mapping, key: &str) -> Option<Vec<String>> {
+    let list: Vec<String> = match map.get(key)? {
+        Value::Sequence(items) => items.iter().filter_map(yaml_str).collect(),
+        value => yaml_str(value)?.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+    };
+    Some(list).filter(|l| !l.is_empty())
+}
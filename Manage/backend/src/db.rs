@@ -1,5 +1,6 @@
 use worker::*;
 use worker::d1::D1Database;
+use worker::wasm_bindgen::JsValue;
 use crate::models::User;
 
 pub async fn get_user_by_username(db: &D1Database, username: &str) -> Result<Option<User>> {
@@ -81,46 +82,277 @@ pub async fn update_user(db: &D1Database, user_id: i64, username: &str, password
 
 // ============= 代理节点操作 =============
 
-pub async fn list_proxy_nodes(db: &D1Database) -> Result<Vec<crate::models::ProxyNode>> {
-    let stmt = db.prepare("SELECT id, name, protocol, address, port, username, password, extra_config, group_name, status, latency, last_check_at, created_at, updated_at FROM proxy_nodes ORDER BY id DESC");
+// 节点所在分组的名称，未分组时为空字符串
+const NODE_GROUP_NAME: &str = "COALESCE((SELECT g.name FROM node_groups g WHERE g.id = proxy_nodes.group_id), '')";
+
+// 最近 24 小时可用率（百分比），没有检测记录时为 NULL
+const UPTIME_24H_SUBQUERY: &str = "(SELECT ROUND(100.0 * SUM(CASE WHEN c.status = 'valid' THEN 1 ELSE 0 END) / COUNT(*), 2) FROM proxy_node_checks c WHERE c.node_id = proxy_nodes.id AND c.checked_at >= datetime('now', '-1 day'))";
+
+// 节点列表排序方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeSort {
+    IdDesc,
+    IdAsc,
+    NameAsc,
+    NameDesc,
+    LatencyAsc,
+    LatencyDesc,
+    Manual,
+}
+
+impl NodeSort {
+    pub fn from_param(value: &str) -> Option<Self> {
+        match value {
+            "id_desc" => Some(NodeSort::IdDesc),
+            "id_asc" => Some(NodeSort::IdAsc),
+            "name_asc" => Some(NodeSort::NameAsc),
+            "name_desc" => Some(NodeSort::NameDesc),
+            "latency_asc" => Some(NodeSort::LatencyAsc),
+            "latency_desc" => Some(NodeSort::LatencyDesc),
+            "manual" => Some(NodeSort::Manual),
+            _ => None,
+        }
+    }
+    
+    // 排序键表达式，未检测的节点（latency 为 NULL）始终排在最后
+    fn key_expr(&self) -> &'static str {
+        match self {
+            NodeSort::IdDesc | NodeSort::IdAsc => "id",
+            NodeSort::NameAsc | NodeSort::NameDesc => "name",
+            NodeSort::LatencyAsc => "COALESCE(latency, 2147483647)",
+            NodeSort::LatencyDesc => "COALESCE(latency, -1)",
+            NodeSort::Manual => "sort_order",
+        }
+    }
+    
+    fn descending(&self) -> bool {
+        matches!(self, NodeSort::IdDesc | NodeSort::NameDesc | NodeSort::LatencyDesc)
+    }
+    
+    // 节点在该排序下的键值，用于生成下一页的 cursor
+    pub fn key_of(&self, node: &crate::models::ProxyNode) -> serde_json::Value {
+        match self {
+            NodeSort::IdDesc | NodeSort::IdAsc => node.id.into(),
+            NodeSort::NameAsc | NodeSort::NameDesc => node.name.clone().into(),
+            NodeSort::LatencyAsc => node.latency.unwrap_or(2147483647).into(),
+            NodeSort::LatencyDesc => node.latency.unwrap_or(-1).into(),
+            NodeSort::Manual => node.sort_order.into(),
+        }
+    }
+}
+
+// 分页游标：上一页最后一个节点的排序键与 id
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct NodeCursor {
+    pub key: serde_json::Value,
+    pub id: i64,
+}
+
+// 按筛选条件分页列出节点（keyset 分页）
+pub async fn list_proxy_nodes_page(
+    db: &D1Database,
+    filter: &crate::models::ProxyNodeFilter,
+    sort: NodeSort,
+    cursor: Option<&NodeCursor>,
+    limit: usize,
+) -> Result<Vec<crate::models::ProxyNode>> {
+    let (mut where_clause, mut params) = build_node_filter(filter, None);
+    
+    if let Some(cursor) = cursor {
+        let key_param: JsValue = match &cursor.key {
+            serde_json::Value::Number(n) => n.as_f64().unwrap_or(0.0).into(),
+            serde_json::Value::String(s) => s.as_str().into(),
+            _ => return Err(Error::RustError("Invalid cursor".to_string())),
+        };
+        let op = if sort.descending() { "<" } else { ">" };
+        let condition = format!("({0} {1} ? OR ({0} = ? AND id {1} {2}))", sort.key_expr(), op, cursor.id);
+        where_clause = if where_clause.is_empty() {
+            format!(" WHERE {}", condition)
+        } else {
+            format!("{} AND {}", where_clause, condition)
+        };
+        params.push(key_param.clone());
+        params.push(key_param);
+    }
+    
+    let direction = if sort.descending() { "DESC" } else { "ASC" };
+    let query = format!(
+        "SELECT id, name, protocol, address, port, username, password, extra_config, group_id, {5} AS group_name, status, latency, last_check_at, last_error, fingerprint, stale_at, tags, region, enabled, sort_order, created_at, updated_at, {0} AS uptime_24h FROM proxy_nodes{1} ORDER BY {2} {3}, id {3} LIMIT {4}",
+        UPTIME_24H_SUBQUERY, where_clause, sort.key_expr(), direction, limit, NODE_GROUP_NAME
+    );
+    let stmt = db.prepare(&query).bind(&params)?;
     let result = stmt.all().await?;
     
     let nodes: Vec<crate::models::ProxyNode> = result.results()?;
     Ok(nodes)
 }
 
+// 满足筛选条件的节点总数
+pub async fn count_proxy_nodes(db: &D1Database, filter: &crate::models::ProxyNodeFilter) -> Result<i64> {
+    let (where_clause, params) = build_node_filter(filter, None);
+    let query = format!("SELECT COUNT(*) AS count FROM proxy_nodes{}", where_clause);
+    let result = db.prepare(&query)
+        .bind(&params)?
+        .first::<i64>(Some("count"))
+        .await?;
+    Ok(result.unwrap_or(0))
+}
+
+// 按某一列统计节点数量，统计时忽略该列自身的筛选条件
+pub async fn count_proxy_node_facet(
+    db: &D1Database,
+    filter: &crate::models::ProxyNodeFilter,
+    column: &str,
+) -> Result<Vec<crate::models::FacetCount>> {
+    let (where_clause, params) = build_node_filter(filter, Some(column));
+    let expr = if column == "group_name" { NODE_GROUP_NAME } else { column };
+    let query = format!(
+        "SELECT COALESCE({0}, '') AS value, COUNT(*) AS count FROM proxy_nodes{1} GROUP BY COALESCE({0}, '') ORDER BY count DESC, value",
+        expr, where_clause
+    );
+    let result = db.prepare(&query).bind(&params)?.all().await?;
+    let counts: Vec<crate::models::FacetCount> = result.results()?;
+    Ok(counts)
+}
+
+// 按标签统计节点数量，统计时忽略标签自身的筛选条件
+pub async fn count_proxy_node_tags(db: &D1Database, filter: &crate::models::ProxyNodeFilter) -> Result<Vec<crate::models::FacetCount>> {
+    let (where_clause, params) = build_node_filter(filter, Some("tags"));
+    let query = format!(
+        "SELECT t.value AS value, COUNT(*) AS count FROM (SELECT tags FROM proxy_nodes{}) AS n, json_each(COALESCE(NULLIF(n.tags, ''), '[]')) AS t GROUP BY t.value ORDER BY count DESC, value",
+        where_clause
+    );
+    let result = db.prepare(&query).bind(&params)?.all().await?;
+    let counts: Vec<crate::models::FacetCount> = result.results()?;
+    Ok(counts)
+}
+
+// 按筛选条件列出全部节点
+pub async fn list_proxy_nodes_filtered(db: &D1Database, filter: &crate::models::ProxyNodeFilter) -> Result<Vec<crate::models::ProxyNode>> {
+    let (where_clause, params) = build_node_filter(filter, None);
+    let query = format!(
+        "SELECT id, name, protocol, address, port, username, password, extra_config, group_id, {} AS group_name, status, latency, last_check_at, last_error, fingerprint, stale_at, tags, region, enabled, sort_order, created_at, updated_at FROM proxy_nodes{} ORDER BY id DESC",
+        NODE_GROUP_NAME, where_clause
+    );
+    let stmt = db.prepare(&query).bind(&params)?;
+    let result = stmt.all().await?;
+    
+    let nodes: Vec<crate::models::ProxyNode> = result.results()?;
+    Ok(nodes)
+}
+
+// 生成筛选条件的 WHERE 子句与绑定参数，skip_column 对应的条件不参与（用于分面统计）
+fn build_node_filter(filter: &crate::models::ProxyNodeFilter, skip_column: Option<&str>) -> (String, Vec<JsValue>) {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params: Vec<JsValue> = Vec::new();
+    
+    if let Some(ref group_name) = filter.group_name {
+        if skip_column != Some("group_name") {
+            conditions.push(format!("{} = ?", NODE_GROUP_NAME));
+            params.push(group_name.as_str().into());
+        }
+    }
+    if let Some(ref protocol) = filter.protocol {
+        if skip_column != Some("protocol") {
+            conditions.push("protocol = ?".to_string());
+            params.push(protocol.as_str().into());
+        }
+    }
+    if let Some(ref status) = filter.status {
+        if skip_column != Some("status") {
+            conditions.push("status = ?".to_string());
+            params.push(status.as_str().into());
+        }
+    }
+    if let Some(search) = filter.search.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        // 转义 LIKE 通配符
+        let pattern = format!(
+            "%{}%",
+            search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        conditions.push("(name LIKE ? ESCAPE '\\' OR address LIKE ? ESCAPE '\\')".to_string());
+        params.push(pattern.as_str().into());
+        params.push(pattern.as_str().into());
+    }
+    if let Some(ref region) = filter.region {
+        if skip_column != Some("region") {
+            conditions.push("COALESCE(region, '') = ?".to_string());
+            params.push(region.as_str().into());
+        }
+    }
+    if let Some(ref tags) = filter.tags {
+        if skip_column != Some("tags") {
+            for tag in tags {
+                conditions.push("EXISTS (SELECT 1 FROM json_each(COALESCE(NULLIF(proxy_nodes.tags, ''), '[]')) WHERE value = ?)".to_string());
+                params.push(tag.as_str().into());
+            }
+        }
+    }
+    if let Some(enabled) = filter.enabled {
+        conditions.push(format!("COALESCE(enabled, 1) = {}", if enabled { 1 } else { 0 }));
+    }
+    if let Some(max_latency) = filter.max_latency {
+        conditions.push(format!("latency IS NOT NULL AND latency <= {}", max_latency));
+    }
+    if let Some(ref ids) = filter.ids {
+        let id_list = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+        // 空列表不匹配任何节点
+        conditions.push(format!("id IN ({})", if id_list.is_empty() { "NULL".to_string() } else { id_list }));
+    }
+    
+    if conditions.is_empty() {
+        (String::new(), params)
+    } else {
+        (format!(" WHERE {}", conditions.join(" AND ")), params)
+    }
+}
+
+// 可为空的 id 写入 SQL
+fn sql_id(id: Option<i64>) -> String {
+    id.map(|id| id.to_string()).unwrap_or_else(|| "NULL".to_string())
+}
+
 pub async fn get_proxy_node(db: &D1Database, id: i64) -> Result<Option<crate::models::ProxyNode>> {
-    let query = format!("SELECT id, name, protocol, address, port, username, password, extra_config, group_name, status, latency, last_check_at, created_at, updated_at FROM proxy_nodes WHERE id = {}", id);
+    let query = format!("SELECT id, name, protocol, address, port, username, password, extra_config, group_id, {} AS group_name, status, latency, last_check_at, last_error, fingerprint, stale_at, tags, region, enabled, sort_order, created_at, updated_at, {} AS uptime_24h FROM proxy_nodes WHERE id = {}", NODE_GROUP_NAME, UPTIME_24H_SUBQUERY, id);
     let stmt = db.prepare(&query);
     let result = stmt.first::<crate::models::ProxyNode>(None).await?;
     Ok(result)
 }
 
-pub async fn create_proxy_node(
-    db: &D1Database,
-    name: &str,
-    protocol: &str,
-    address: &str,
-    port: i64,
-    username: Option<&str>,
-    password: Option<&str>,
-    extra_config: Option<&str>,
-    group_name: Option<&str>,
-) -> Result<i64> {
-    let stmt = db.prepare(
-        "INSERT INTO proxy_nodes (name, protocol, address, port, username, password, extra_config, group_name, status, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'unknown', datetime('now'), datetime('now'))"
+// 创建节点时写入的字段
+pub struct NewProxyNode<'a> {
+    pub name: &'a str,
+    pub protocol: &'a str,
+    pub address: &'a str,
+    pub port: i64,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+    pub extra_config: Option<&'a str>,
+    pub group_id: Option<i64>,
+    pub fingerprint: &'a str,
+    pub tags: &'a str,
+    pub region: Option<&'a str>,
+}
+
+pub async fn create_proxy_node(db: &D1Database, node: &NewProxyNode<'_>) -> Result<i64> {
+    let query = format!(
+        "INSERT INTO proxy_nodes (name, protocol, address, port, username, password, extra_config, group_id, fingerprint, tags, region, sort_order, status, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, {}, ?, ?, NULLIF(?, ''), (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM proxy_nodes), 'unknown', datetime('now'), datetime('now'))",
+        sql_id(node.group_id)
     );
+    let stmt = db.prepare(&query);
     
-    let port_str = port.to_string();
+    let port_str = node.port.to_string();
     stmt.bind(&[
-        name.into(),
-        protocol.into(),
-        address.into(),
+        node.name.into(),
+        node.protocol.into(),
+        node.address.into(),
         port_str.as_str().into(),
-        username.unwrap_or("").into(),
-        password.unwrap_or("").into(),
-        extra_config.unwrap_or("").into(),
-        group_name.unwrap_or("").into(),
+        node.username.unwrap_or("").into(),
+        node.password.unwrap_or("").into(),
+        node.extra_config.unwrap_or("").into(),
+        node.fingerprint.into(),
+        node.tags.into(),
+        node.region.unwrap_or("").into(),
     ])?
     .run()
     .await?;
@@ -138,71 +370,455 @@ pub async fn create_proxy_node(
     }
 }
 
-pub async fn update_proxy_node(
-    db: &D1Database,
-    id: i64,
-    name: &str,
-    protocol: &str,
-    address: &str,
-    port: i64,
-    username: Option<&str>,
-    password: Option<&str>,
-    extra_config: Option<&str>,
-    group_name: Option<&str>,
-) -> Result<()> {
+// 修改节点时写入的字段，group_id、region 为 None 时保持原值
+pub struct ProxyNodeUpdate<'a> {
+    pub name: &'a str,
+    pub protocol: &'a str,
+    pub address: &'a str,
+    pub port: i64,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+    pub extra_config: Option<&'a str>,
+    pub group_id: Option<Option<i64>>,
+    pub fingerprint: &'a str,
+    pub tags: Option<&'a str>,
+    pub region: Option<Option<&'a str>>,
+}
+
+pub async fn update_proxy_node(db: &D1Database, id: i64, node: &ProxyNodeUpdate<'_>) -> Result<()> {
+    // group_id、region 未提供、tags 为空时保持原值
+    let group_set = node.group_id.map(|id| format!("group_id = {}, ", sql_id(id))).unwrap_or_default();
+    let region_set = if node.region.is_some() { ", region = NULLIF(?, '')" } else { "" };
     let query = format!(
-        "UPDATE proxy_nodes SET name = ?, protocol = ?, address = ?, port = {}, username = ?, password = ?, extra_config = ?, group_name = ?, updated_at = datetime('now') WHERE id = {}",
-        port, id
+        "UPDATE proxy_nodes SET name = ?, protocol = ?, address = ?, port = {}, username = ?, password = ?, extra_config = ?, {}fingerprint = ?, tags = COALESCE(NULLIF(?, ''), tags){}, updated_at = datetime('now') WHERE id = {}",
+        node.port, group_set, region_set, id
     );
+    let mut params: Vec<JsValue> = vec![
+        node.name.into(),
+        node.protocol.into(),
+        node.address.into(),
+        node.username.unwrap_or("").into(),
+        node.password.unwrap_or("").into(),
+        node.extra_config.unwrap_or("").into(),
+        node.fingerprint.into(),
+        node.tags.unwrap_or("").into(),
+    ];
+    if let Some(region) = node.region {
+        params.push(region.unwrap_or("").into());
+    }
     let stmt = db.prepare(&query);
-    stmt.bind(&[
-        name.into(),
-        protocol.into(),
-        address.into(),
-        username.unwrap_or("").into(),
-        password.unwrap_or("").into(),
-        extra_config.unwrap_or("").into(),
-        group_name.unwrap_or("").into(),
-    ])?
-    .run()
-    .await?;
+    stmt.bind(&params)?
+        .run()
+        .await?;
     Ok(())
 }
 
 pub async fn delete_proxy_node(db: &D1Database, id: i64) -> Result<()> {
-    let query = format!("DELETE FROM proxy_nodes WHERE id = {}", id);
-    let stmt = db.prepare(&query);
-    stmt.run().await?;
+    let statements = vec![
+        db.prepare(format!("DELETE FROM proxy_node_checks WHERE node_id = {}", id)),
+        db.prepare(format!("DELETE FROM proxy_node_check_hourly WHERE node_id = {}", id)),
+        db.prepare(format!("DELETE FROM proxy_nodes WHERE id = {}", id)),
+    ];
+    db.batch(statements).await?;
     Ok(())
 }
 
-pub async fn update_proxy_node_status(db: &D1Database, id: i64, status: &str, latency: Option<i64>) -> Result<()> {
+// 更新节点检测状态，并写入一条检测历史
+pub async fn update_proxy_node_status(
+    db: &D1Database,
+    id: i64,
+    status: &str,
+    latency: Option<i64>,
+    last_error: Option<&str>,
+) -> Result<()> {
     let latency_str = latency.map(|l| l.to_string()).unwrap_or_else(|| "NULL".to_string());
-    let query = format!(
-        "UPDATE proxy_nodes SET status = ?, latency = {}, last_check_at = datetime('now'), updated_at = datetime('now') WHERE id = {}",
+    let update_query = format!(
+        "UPDATE proxy_nodes SET status = ?, latency = {}, last_error = NULLIF(?, ''), last_check_at = datetime('now'), updated_at = datetime('now') WHERE id = {}",
         latency_str, id
     );
-    let stmt = db.prepare(&query);
-    stmt.bind(&[status.into()])?
-        .run()
+    let update = db.prepare(&update_query)
+        .bind(&[status.into(), last_error.unwrap_or("").into()])?;
+    
+    // 未检测的节点（UDP 协议）不写入检测历史，以免拉低在线率
+    if status == "unknown" {
+        update.run().await?;
+        return Ok(());
+    }
+    
+    let insert_query = format!(
+        "INSERT INTO proxy_node_checks (node_id, status, latency, message, checked_at) VALUES ({}, ?, {}, NULLIF(?, ''), datetime('now'))",
+        id, latency_str
+    );
+    let insert = db.prepare(&insert_query)
+        .bind(&[status.into(), last_error.unwrap_or("").into()])?;
+    
+    db.batch(vec![update, insert]).await?;
+    Ok(())
+}
+
+// D1 单条语句最多绑定 100 个参数
+const D1_MAX_BOUND_PARAMS: usize = 100;
+
+// 批量写入检测结果：每个节点一条 UPDATE，检测历史合并为多行 INSERT，整体作为一个 D1 batch 执行
+pub async fn batch_update_proxy_node_status(db: &D1Database, results: &[crate::models::ProxyCheckResult]) -> Result<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+    
+    let mut statements = Vec::with_capacity(results.len() + 1);
+    
+    for result in results {
+        let latency_str = result.latency.map(|l| l.to_string()).unwrap_or_else(|| "NULL".to_string());
+        let query = format!(
+            "UPDATE proxy_nodes SET status = ?, latency = {}, last_error = NULLIF(?, ''), last_check_at = datetime('now'), updated_at = datetime('now') WHERE id = {}",
+            latency_str, result.id
+        );
+        statements.push(db.prepare(&query).bind(&[result.status.as_str().into(), check_error(result).into()])?);
+    }
+    
+    // 每行绑定 status 与 message 两个参数，未检测的节点不写入检测历史
+    let checked: Vec<&crate::models::ProxyCheckResult> = results.iter().filter(|r| r.status != "unknown").collect();
+    for chunk in checked.chunks(D1_MAX_BOUND_PARAMS / 2) {
+        let mut rows: Vec<String> = Vec::with_capacity(chunk.len());
+        let mut params: Vec<JsValue> = Vec::with_capacity(chunk.len() * 2);
+        
+        for result in chunk {
+            let latency_str = result.latency.map(|l| l.to_string()).unwrap_or_else(|| "NULL".to_string());
+            rows.push(format!("({}, ?, {}, NULLIF(?, ''), datetime('now'))", result.id, latency_str));
+            params.push(result.status.as_str().into());
+            params.push(check_error(result).into());
+        }
+        
+        let query = format!(
+            "INSERT INTO proxy_node_checks (node_id, status, latency, message, checked_at) VALUES {}",
+            rows.join(", ")
+        );
+        statements.push(db.prepare(&query).bind(&params)?);
+    }
+    
+    db.batch(statements).await?;
+    Ok(())
+}
+
+// 导入时写入的节点字段
+pub struct ImportedNode {
+    pub name: String,
+    pub protocol: String,
+    pub address: String,
+    pub port: i64,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub extra_config: Option<String>,
+    pub fingerprint: String,
+    pub region: Option<String>,
+}
+
+// 在一个批次中写入导入结果：新增节点、按 id 更新已有节点，
+// 订阅中重新出现的节点清除失效标记，消失的节点删除或标记为失效
+pub async fn apply_proxy_node_import(
+    db: &D1Database,
+    group_name: Option<&str>,
+    inserts: &[ImportedNode],
+    updates: &[(i64, ImportedNode)],
+    matched_ids: &[i64],
+    vanished_ids: &[i64],
+    remove_vanished: bool,
+) -> Result<()> {
+    let mut statements = Vec::with_capacity(inserts.len() + updates.len() + 4);
+    
+    if let Some(name) = group_name.filter(|n| !n.is_empty()) {
+        statements.push(db.prepare("INSERT OR IGNORE INTO node_groups (name) VALUES (?)").bind(&[name.into()])?);
+    }
+    
+    for node in inserts {
+        let query = format!(
+            "INSERT INTO proxy_nodes (name, protocol, address, port, username, password, extra_config, group_id, fingerprint, region, sort_order, status, created_at, updated_at) VALUES (?, ?, ?, {}, ?, ?, ?, (SELECT id FROM node_groups WHERE name = ?), ?, NULLIF(?, ''), (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM proxy_nodes), 'unknown', datetime('now'), datetime('now'))",
+            node.port
+        );
+        statements.push(db.prepare(&query).bind(&[
+            node.name.as_str().into(),
+            node.protocol.as_str().into(),
+            node.address.as_str().into(),
+            node.username.as_deref().unwrap_or("").into(),
+            node.password.as_deref().unwrap_or("").into(),
+            node.extra_config.as_deref().unwrap_or("").into(),
+            group_name.unwrap_or("").into(),
+            node.fingerprint.as_str().into(),
+            node.region.as_deref().unwrap_or("").into(),
+        ])?);
+    }
+    
+    // 保留 id 与检测历史，只更新节点内容
+    for (id, node) in updates {
+        let query = format!(
+            "UPDATE proxy_nodes SET name = ?, protocol = ?, address = ?, port = {}, username = ?, password = ?, extra_config = ?, fingerprint = ?, region = NULLIF(?, ''), updated_at = datetime('now') WHERE id = {}",
+            node.port, id
+        );
+        statements.push(db.prepare(&query).bind(&[
+            node.name.as_str().into(),
+            node.protocol.as_str().into(),
+            node.address.as_str().into(),
+            node.username.as_deref().unwrap_or("").into(),
+            node.password.as_deref().unwrap_or("").into(),
+            node.extra_config.as_deref().unwrap_or("").into(),
+            node.fingerprint.as_str().into(),
+            node.region.as_deref().unwrap_or("").into(),
+        ])?);
+    }
+    
+    if !matched_ids.is_empty() {
+        let id_list = matched_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+        statements.push(db.prepare(format!("UPDATE proxy_nodes SET stale_at = NULL WHERE id IN ({}) AND stale_at IS NOT NULL", id_list)));
+    }
+    
+    if !vanished_ids.is_empty() {
+        let id_list = vanished_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+        if remove_vanished {
+            statements.push(db.prepare(format!("DELETE FROM proxy_node_checks WHERE node_id IN ({})", id_list)));
+            statements.push(db.prepare(format!("DELETE FROM proxy_node_check_hourly WHERE node_id IN ({})", id_list)));
+            statements.push(db.prepare(format!("DELETE FROM proxy_nodes WHERE id IN ({})", id_list)));
+        } else {
+            // 保留最早失效的时间
+            statements.push(db.prepare(format!(
+                "UPDATE proxy_nodes SET stale_at = COALESCE(stale_at, datetime('now')) WHERE id IN ({})",
+                id_list
+            )));
+        }
+    }
+    
+    if statements.is_empty() {
+        return Ok(());
+    }
+    
+    db.batch(statements).await?;
+    Ok(())
+}
+
+// 规范化后的节点协议配置
+pub struct NodeConfigUpdate {
+    pub id: i64,
+    pub protocol: String,
+    pub username: Option<String>,
+    pub extra_config: Option<String>,
+    pub fingerprint: String,
+}
+
+// 批量写回规范化后的协议配置
+pub async fn batch_update_proxy_node_configs(db: &D1Database, updates: &[NodeConfigUpdate]) -> Result<()> {
+    if updates.is_empty() {
+        return Ok(());
+    }
+    
+    let mut statements = Vec::with_capacity(updates.len());
+    
+    for update in updates {
+        let query = format!(
+            "UPDATE proxy_nodes SET protocol = ?, username = ?, extra_config = ?, fingerprint = ?, updated_at = datetime('now') WHERE id = {}",
+            update.id
+        );
+        statements.push(db.prepare(&query).bind(&[
+            update.protocol.as_str().into(),
+            update.username.as_deref().unwrap_or("").into(),
+            update.extra_config.as_deref().unwrap_or("").into(),
+            update.fingerprint.as_str().into(),
+        ])?);
+    }
+    
+    db.batch(statements).await?;
+    Ok(())
+}
+
+// 一次性数据迁移上次全部成功的完成时间，未执行过或存在失败时为 None
+pub async fn get_completed_data_migration(db: &D1Database, name: &str) -> Result<Option<String>> {
+    let completed_at = db.prepare("SELECT completed_at FROM data_migrations WHERE name = ? AND failed = 0")
+        .bind(&[name.into()])?
+        .first::<String>(Some("completed_at"))
         .await?;
+    Ok(completed_at)
+}
+
+// 记录一次性数据迁移的执行结果
+pub async fn record_data_migration(db: &D1Database, name: &str, migrated: usize, failed: usize) -> Result<()> {
+    let query = format!(
+        "INSERT INTO data_migrations (name, migrated, failed, completed_at) VALUES (?, {}, {}, datetime('now')) \
+         ON CONFLICT(name) DO UPDATE SET migrated = excluded.migrated, failed = excluded.failed, completed_at = excluded.completed_at",
+        migrated, failed
+    );
+    db.prepare(&query).bind(&[name.into()])?.run().await?;
     Ok(())
 }
 
+// 批量修改时单个节点需要更新的字段，未设置的字段保持不变
+#[derive(Default)]
+pub struct NodeBatchUpdate {
+    pub id: i64,
+    pub name: Option<String>,
+    pub address: Option<String>,
+    pub port: Option<i64>,
+    // Some(None) 表示移出分组
+    pub group_id: Option<Option<i64>>,
+    pub fingerprint: Option<String>,
+}
+
+// 在一个批次中写入批量修改
+pub async fn batch_update_proxy_nodes(db: &D1Database, updates: &[NodeBatchUpdate]) -> Result<()> {
+    if updates.is_empty() {
+        return Ok(());
+    }
+    
+    let mut statements = Vec::with_capacity(updates.len());
+    
+    for update in updates {
+        let mut sets: Vec<String> = Vec::new();
+        let mut params: Vec<JsValue> = Vec::new();
+        for (column, value) in [
+            ("name", &update.name),
+            ("address", &update.address),
+            ("fingerprint", &update.fingerprint),
+        ] {
+            if let Some(value) = value {
+                sets.push(format!("{} = ?", column));
+                params.push(value.as_str().into());
+            }
+        }
+        if let Some(port) = update.port {
+            sets.push(format!("port = {}", port));
+        }
+        if let Some(group_id) = update.group_id {
+            sets.push(format!("group_id = {}", sql_id(group_id)));
+        }
+        if sets.is_empty() {
+            continue;
+        }
+        
+        let query = format!(
+            "UPDATE proxy_nodes SET {}, updated_at = datetime('now') WHERE id = {}",
+            sets.join(", "), update.id
+        );
+        statements.push(db.prepare(&query).bind(&params)?);
+    }
+    
+    db.batch(statements).await?;
+    Ok(())
+}
+
+// 检测失败的原因，检测成功或未检测时为空
+fn check_error(result: &crate::models::ProxyCheckResult) -> &str {
+    if result.status == "invalid" { result.message.as_str() } else { "" }
+}
+
 pub async fn batch_delete_proxy_nodes(db: &D1Database, ids: &[i64]) -> Result<usize> {
     if ids.is_empty() {
         return Ok(0);
     }
     
     let id_list = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
-    let query = format!("DELETE FROM proxy_nodes WHERE id IN ({})", id_list);
-    let stmt = db.prepare(&query);
-    stmt.run().await?;
+    let statements = vec![
+        db.prepare(format!("DELETE FROM proxy_node_checks WHERE node_id IN ({})", id_list)),
+        db.prepare(format!("DELETE FROM proxy_node_check_hourly WHERE node_id IN ({})", id_list)),
+        db.prepare(format!("DELETE FROM proxy_nodes WHERE id IN ({})", id_list)),
+    ];
+    db.batch(statements).await?;
     Ok(ids.len())
 }
 
+// ============= 检测历史操作 =============
+
+#[derive(serde::Deserialize)]
+struct CheckCounts {
+    total: Option<i64>,
+    valid: Option<i64>,
+}
+
+// 统计 since 之后的检测次数与成功次数（原始记录 + 小时聚合），
+// since 所在的小时整体计入，不丢弃窗口开头不满一小时的聚合
+pub async fn count_proxy_node_checks(db: &D1Database, node_id: i64, since: &str) -> Result<(i64, i64)> {
+    let raw_query = format!(
+        "SELECT COUNT(*) AS total, SUM(CASE WHEN status = 'valid' THEN 1 ELSE 0 END) AS valid FROM proxy_node_checks WHERE node_id = {} AND checked_at >= ?",
+        node_id
+    );
+    let raw = db.prepare(&raw_query)
+        .bind(&[since.into()])?
+        .first::<CheckCounts>(None)
+        .await?;
+    
+    let hourly_query = format!(
+        "SELECT SUM(total_checks) AS total, SUM(valid_checks) AS valid FROM proxy_node_check_hourly WHERE node_id = {} AND hour >= strftime('%Y-%m-%d %H:00:00', ?)",
+        node_id
+    );
+    let hourly = db.prepare(&hourly_query)
+        .bind(&[since.into()])?
+        .first::<CheckCounts>(None)
+        .await?;
+    
+    let mut total = 0;
+    let mut valid = 0;
+    for counts in [raw, hourly].into_iter().flatten() {
+        total += counts.total.unwrap_or(0);
+        valid += counts.valid.unwrap_or(0);
+    }
+    Ok((total, valid))
+}
+
+// since 之后成功检测的延迟，按升序排列；只有原始记录保留单次延迟，
+// since 早于原始记录的保留期时结果只覆盖保留期
+pub async fn list_proxy_node_latencies(db: &D1Database, node_id: i64, since: &str) -> Result<Vec<i64>> {
+    #[derive(serde::Deserialize)]
+    struct Row {
+        latency: i64,
+    }
+    
+    let query = format!(
+        "SELECT latency FROM proxy_node_checks WHERE node_id = {} AND checked_at >= ? AND status = 'valid' AND latency IS NOT NULL ORDER BY latency",
+        node_id
+    );
+    let result = db.prepare(&query)
+        .bind(&[since.into()])?
+        .all()
+        .await?;
+    let rows: Vec<Row> = result.results()?;
+    Ok(rows.into_iter().map(|r| r.latency).collect())
+}
+
+// 最近的检测记录，按时间倒序
+pub async fn list_recent_proxy_node_checks(db: &D1Database, node_id: i64, limit: u32) -> Result<Vec<crate::models::ProxyNodeCheck>> {
+    let query = format!(
+        "SELECT status, latency, message, checked_at FROM proxy_node_checks WHERE node_id = {} ORDER BY id DESC LIMIT {}",
+        node_id, limit
+    );
+    let result = db.prepare(&query).all().await?;
+    let checks: Vec<crate::models::ProxyNodeCheck> = result.results()?;
+    Ok(checks)
+}
+
+// 将 cutoff 之前的原始检测记录聚合到小时表并删除，同时清理 hourly_cutoff 之前的聚合数据
+pub async fn downsample_proxy_node_checks(db: &D1Database, cutoff: &str, hourly_cutoff: &str) -> Result<()> {
+    let aggregate = db.prepare(
+        "INSERT INTO proxy_node_check_hourly (node_id, hour, total_checks, valid_checks, latency_sum, latency_count, latency_min, latency_max) \
+         SELECT node_id, strftime('%Y-%m-%d %H:00:00', checked_at), COUNT(*), SUM(CASE WHEN status = 'valid' THEN 1 ELSE 0 END), COALESCE(SUM(latency), 0), COUNT(latency), MIN(latency), MAX(latency) \
+         FROM proxy_node_checks WHERE checked_at < ? GROUP BY node_id, strftime('%Y-%m-%d %H:00:00', checked_at) \
+         ON CONFLICT(node_id, hour) DO UPDATE SET \
+         total_checks = total_checks + excluded.total_checks, \
+         valid_checks = valid_checks + excluded.valid_checks, \
+         latency_sum = latency_sum + excluded.latency_sum, \
+         latency_count = latency_count + excluded.latency_count, \
+         latency_min = MIN(COALESCE(latency_min, excluded.latency_min), COALESCE(excluded.latency_min, latency_min)), \
+         latency_max = MAX(COALESCE(latency_max, excluded.latency_max), COALESCE(excluded.latency_max, latency_max))"
+    ).bind(&[cutoff.into()])?;
+    let delete_raw = db.prepare("DELETE FROM proxy_node_checks WHERE checked_at < ?")
+        .bind(&[cutoff.into()])?;
+    let delete_hourly = db.prepare("DELETE FROM proxy_node_check_hourly WHERE hour < ?")
+        .bind(&[hourly_cutoff.into()])?;
+    
+    db.batch(vec![aggregate, delete_raw, delete_hourly]).await?;
+    Ok(())
+}
+
 // ============= 订阅信息操作 =============
 
+// 订阅信息通过 group_id 关联分组，分组名称取自 node_groups
+const SUBSCRIPTION_INFO_SELECT: &str = "SELECT s.id, g.name AS group_name, s.subscription_url, s.upload_bytes, s.download_bytes, s.total_bytes, s.expire_timestamp, s.last_update_at, s.created_at, s.refresh_enabled, s.refresh_interval_minutes, s.last_refresh_at, s.last_refresh_status, s.last_refresh_added, s.last_refresh_updated, s.last_refresh_vanished, s.last_refresh_error FROM subscription_info s JOIN node_groups g ON g.id = s.group_id";
+
 pub async fn upsert_subscription_info(
     db: &D1Database,
     group_name: &str,
@@ -215,27 +831,315 @@ pub async fn upsert_subscription_info(
     let expire_str = expire_timestamp.map(|t| t.to_string()).unwrap_or_else(|| "NULL".to_string());
     let url_value = subscription_url.unwrap_or("");
     
-    // 使用 INSERT OR REPLACE 实现 upsert
+    // 使用 ON CONFLICT 实现 upsert，保留刷新设置等其他列；分组不存在时不写入
     let query = format!(
-        "INSERT OR REPLACE INTO subscription_info (group_name, subscription_url, upload_bytes, download_bytes, total_bytes, expire_timestamp, last_update_at, created_at) VALUES (?, ?, {}, {}, {}, {}, datetime('now'), COALESCE((SELECT created_at FROM subscription_info WHERE group_name = ?), datetime('now')))",
+        "INSERT INTO subscription_info (group_id, subscription_url, upload_bytes, download_bytes, total_bytes, expire_timestamp, last_update_at, created_at) \
+         SELECT id, ?, {0}, {1}, {2}, {3}, datetime('now'), datetime('now') FROM node_groups WHERE name = ? \
+         ON CONFLICT(group_id) DO UPDATE SET subscription_url = excluded.subscription_url, upload_bytes = {0}, download_bytes = {1}, total_bytes = {2}, expire_timestamp = {3}, last_update_at = datetime('now')",
         upload_bytes, download_bytes, total_bytes, expire_str
     );
     let stmt = db.prepare(&query);
-    stmt.bind(&[group_name.into(), url_value.into(), group_name.into()])?
+    stmt.bind(&[url_value.into(), group_name.into()])?
         .run()
         .await?;
     Ok(())
 }
 
 pub async fn get_subscription_info(db: &D1Database, group_name: &str) -> Result<Option<crate::models::SubscriptionInfo>> {
-    let stmt = db.prepare("SELECT id, group_name, subscription_url, upload_bytes, download_bytes, total_bytes, expire_timestamp, last_update_at, created_at FROM subscription_info WHERE group_name = ?");
+    let query = format!("{} WHERE g.name = ?", SUBSCRIPTION_INFO_SELECT);
+    let stmt = db.prepare(&query);
     let result = stmt.bind(&[group_name.into()])?.first::<crate::models::SubscriptionInfo>(None).await?;
     Ok(result)
 }
 
 pub async fn list_subscription_info(db: &D1Database) -> Result<Vec<crate::models::SubscriptionInfo>> {
-    let stmt = db.prepare("SELECT id, group_name, subscription_url, upload_bytes, download_bytes, total_bytes, expire_timestamp, last_update_at, created_at FROM subscription_info ORDER BY group_name");
+    let query = format!("{} ORDER BY g.name", SUBSCRIPTION_INFO_SELECT);
+    let stmt = db.prepare(&query);
     let result = stmt.all().await?;
     let infos: Vec<crate::models::SubscriptionInfo> = result.results()?;
     Ok(infos)
 }
+
+// 到期需要定时刷新的订阅，从未刷新过的优先
+pub async fn list_due_subscriptions(db: &D1Database, limit: usize) -> Result<Vec<crate::models::SubscriptionInfo>> {
+    let query = format!(
+        "{} \
+         WHERE s.refresh_enabled = 1 AND COALESCE(s.subscription_url, '') != '' \
+         AND (s.last_refresh_at IS NULL OR s.last_refresh_at <= datetime('now', '-' || s.refresh_interval_minutes || ' minutes')) \
+         ORDER BY s.last_refresh_at ASC LIMIT {}",
+        SUBSCRIPTION_INFO_SELECT, limit
+    );
+    let result = db.prepare(&query).all().await?;
+    let infos: Vec<crate::models::SubscriptionInfo> = result.results()?;
+    Ok(infos)
+}
+
+pub async fn update_subscription_refresh_setting(
+    db: &D1Database,
+    group_name: &str,
+    refresh_enabled: bool,
+    refresh_interval_minutes: i64,
+) -> Result<()> {
+    let query = format!(
+        "UPDATE subscription_info SET refresh_enabled = {}, refresh_interval_minutes = {} WHERE group_id = (SELECT id FROM node_groups WHERE name = ?)",
+        if refresh_enabled { 1 } else { 0 }, refresh_interval_minutes
+    );
+    db.prepare(&query)
+        .bind(&[group_name.into()])?
+        .run()
+        .await?;
+    Ok(())
+}
+
+// 记录最近一次刷新成功的节点变化
+pub async fn record_subscription_refresh_success(
+    db: &D1Database,
+    group_name: &str,
+    added: usize,
+    updated: usize,
+    vanished: usize,
+) -> Result<()> {
+    let query = format!(
+        "UPDATE subscription_info SET last_refresh_at = datetime('now'), last_refresh_status = 'success', last_refresh_added = {}, last_refresh_updated = {}, last_refresh_vanished = {}, last_refresh_error = NULL WHERE group_id = (SELECT id FROM node_groups WHERE name = ?)",
+        added, updated, vanished
+    );
+    db.prepare(&query)
+        .bind(&[group_name.into()])?
+        .run()
+        .await?;
+    Ok(())
+}
+
+// 记录最近一次刷新失败的原因
+pub async fn record_subscription_refresh_error(db: &D1Database, group_name: &str, error: &str) -> Result<()> {
+    db.prepare("UPDATE subscription_info SET last_refresh_at = datetime('now'), last_refresh_status = 'error', last_refresh_added = NULL, last_refresh_updated = NULL, last_refresh_vanished = NULL, last_refresh_error = ? WHERE group_id = (SELECT id FROM node_groups WHERE name = ?)")
+        .bind(&[error.into(), group_name.into()])?
+        .run()
+        .await?;
+    Ok(())
+}
+
+// ============= 节点分组操作 =============
+
+pub async fn list_node_groups(db: &D1Database) -> Result<Vec<crate::models::NodeGroup>> {
+    let stmt = db.prepare(
+        "SELECT g.id, g.name, g.description, g.color, g.sort_order, \
+         (SELECT COUNT(*) FROM proxy_nodes n WHERE n.group_id = g.id) AS node_count, \
+         g.created_at, g.updated_at \
+         FROM node_groups g ORDER BY g.sort_order, g.name"
+    );
+    let result = stmt.all().await?;
+    let groups: Vec<crate::models::NodeGroup> = result.results()?;
+    Ok(groups)
+}
+
+pub async fn get_node_group(db: &D1Database, id: i64) -> Result<Option<crate::models::NodeGroup>> {
+    let query = format!(
+        "SELECT g.id, g.name, g.description, g.color, g.sort_order, \
+         (SELECT COUNT(*) FROM proxy_nodes n WHERE n.group_id = g.id) AS node_count, \
+         g.created_at, g.updated_at \
+         FROM node_groups g WHERE g.id = {}",
+        id
+    );
+    let result = db.prepare(&query).first::<crate::models::NodeGroup>(None).await?;
+    Ok(result)
+}
+
+pub async fn node_group_name_exists(db: &D1Database, name: &str) -> Result<bool> {
+    let result = db.prepare("SELECT COUNT(*) AS count FROM node_groups WHERE name = ?")
+        .bind(&[name.into()])?
+        .first::<i64>(Some("count"))
+        .await?;
+    Ok(result.unwrap_or(0) > 0)
+}
+
+pub async fn create_node_group(
+    db: &D1Database,
+    name: &str,
+    description: Option<&str>,
+    color: Option<&str>,
+    sort_order: i64,
+) -> Result<i64> {
+    let query = format!(
+        "INSERT INTO node_groups (name, description, color, sort_order, created_at, updated_at) VALUES (?, ?, ?, {}, datetime('now'), datetime('now')) RETURNING id",
+        sort_order
+    );
+    let result = db.prepare(&query)
+        .bind(&[name.into(), description.unwrap_or("").into(), color.unwrap_or("").into()])?
+        .first::<i64>(Some("id"))
+        .await?;
+    Ok(result.unwrap_or(0))
+}
+
+// 按名称查找分组 id
+pub async fn get_node_group_id(db: &D1Database, name: &str) -> Result<Option<i64>> {
+    let result = db.prepare("SELECT id FROM node_groups WHERE name = ?")
+        .bind(&[name.into()])?
+        .first::<i64>(Some("id"))
+        .await?;
+    Ok(result)
+}
+
+// 更新分组；节点、订阅信息与检测设置按 group_id 关联，改名不影响它们
+pub async fn update_node_group(
+    db: &D1Database,
+    id: i64,
+    name: &str,
+    description: Option<&str>,
+    color: Option<&str>,
+    sort_order: i64,
+) -> Result<()> {
+    let query = format!(
+        "UPDATE node_groups SET name = ?, description = ?, color = ?, sort_order = {}, updated_at = datetime('now') WHERE id = {}",
+        sort_order, id
+    );
+    db.prepare(&query)
+        .bind(&[name.into(), description.unwrap_or("").into(), color.unwrap_or("").into()])?
+        .run()
+        .await?;
+    Ok(())
+}
+
+// 删除分组：cascade 时连同节点及其检测历史一起删除，
+// 否则将节点移动到 move_to（为空表示移出分组）
+pub async fn delete_node_group(
+    db: &D1Database,
+    id: i64,
+    cascade: bool,
+    move_to: Option<i64>,
+) -> Result<()> {
+    let mut statements = Vec::new();
+    
+    if cascade {
+        for table in ["proxy_node_checks", "proxy_node_check_hourly"] {
+            let query = format!(
+                "DELETE FROM {} WHERE node_id IN (SELECT id FROM proxy_nodes WHERE group_id = {})",
+                table, id
+            );
+            statements.push(db.prepare(&query));
+        }
+        statements.push(db.prepare(format!("DELETE FROM proxy_nodes WHERE group_id = {}", id)));
+    } else {
+        statements.push(db.prepare(format!(
+            "UPDATE proxy_nodes SET group_id = {}, updated_at = datetime('now') WHERE group_id = {}",
+            sql_id(move_to), id
+        )));
+    }
+    
+    // 订阅与检测设置属于分组本身，不随节点移动
+    statements.push(db.prepare(format!("DELETE FROM subscription_info WHERE group_id = {}", id)));
+    statements.push(db.prepare(format!("DELETE FROM group_check_settings WHERE group_id = {}", id)));
+    statements.push(db.prepare(format!("DELETE FROM node_groups WHERE id = {}", id)));
+    
+    db.batch(statements).await?;
+    Ok(())
+}
+
+pub async fn move_proxy_nodes(db: &D1Database, ids: &[i64], group_id: Option<i64>) -> Result<usize> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    
+    let id_list = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+    let query = format!(
+        "UPDATE proxy_nodes SET group_id = {}, updated_at = datetime('now') WHERE id IN ({})",
+        sql_id(group_id), id_list
+    );
+    let result = db.prepare(&query).run().await?;
+    let changed = result.meta()?
+        .and_then(|m| m.changes)
+        .unwrap_or(0);
+    Ok(changed)
+}
+
+// 启用或禁用节点
+pub async fn set_proxy_node_enabled(db: &D1Database, id: i64, enabled: bool) -> Result<bool> {
+    let query = format!(
+        "UPDATE proxy_nodes SET enabled = {}, updated_at = datetime('now') WHERE id = {}",
+        if enabled { 1 } else { 0 }, id
+    );
+    let result = db.prepare(&query).run().await?;
+    let changed = result.meta()?
+        .and_then(|m| m.changes)
+        .unwrap_or(0);
+    Ok(changed > 0)
+}
+
+// 按给定顺序重排节点：沿用这些节点原有的排序值，按新顺序重新分配，
+// 不影响分组中其他节点的位置
+pub async fn reorder_proxy_nodes(db: &D1Database, ordered: &[(i64, i64)]) -> Result<()> {
+    if ordered.is_empty() {
+        return Ok(());
+    }
+    
+    let mut slots: Vec<i64> = ordered.iter().map(|(_, sort_order)| *sort_order).collect();
+    slots.sort_unstable();
+    // 排序值重复时（如同时导入的节点）顺延，保证结果唯一
+    for i in 1..slots.len() {
+        if slots[i] <= slots[i - 1] {
+            slots[i] = slots[i - 1] + 1;
+        }
+    }
+    
+    let cases = ordered.iter()
+        .zip(&slots)
+        .map(|((id, _), slot)| format!("WHEN {} THEN {}", id, slot))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let id_list = ordered.iter().map(|(id, _)| id.to_string()).collect::<Vec<_>>().join(",");
+    let query = format!(
+        "UPDATE proxy_nodes SET sort_order = CASE id {} END, updated_at = datetime('now') WHERE id IN ({})",
+        cases, id_list
+    );
+    db.prepare(&query).run().await?;
+    Ok(())
+}
+
+// ============= 定时检测操作 =============
+
+// 按 id 升序取出 cursor 之后到期需要检测的节点，
+// 分组未配置检测间隔时使用 default_interval_minutes，间隔为 0 的分组不参与检测
+pub async fn list_due_proxy_nodes(
+    db: &D1Database,
+    cursor: i64,
+    default_interval_minutes: i64,
+    limit: usize,
+) -> Result<Vec<crate::models::ProxyNode>> {
+    let query = format!(
+        "SELECT n.id, n.name, n.protocol, n.address, n.port, n.username, n.password, n.extra_config, n.group_id, COALESCE(g.name, '') AS group_name, n.status, n.latency, n.last_check_at, n.last_error, n.fingerprint, n.stale_at, n.tags, n.region, n.enabled, n.sort_order, n.created_at, n.updated_at \
+         FROM proxy_nodes n LEFT JOIN node_groups g ON g.id = n.group_id LEFT JOIN group_check_settings s ON s.group_id IS n.group_id \
+         WHERE n.id > {0} AND COALESCE(n.enabled, 1) = 1 AND COALESCE(s.check_interval_minutes, {1}) > 0 \
+         AND (n.last_check_at IS NULL OR n.last_check_at <= datetime('now', '-' || COALESCE(s.check_interval_minutes, {1}) || ' minutes')) \
+         ORDER BY n.id ASC LIMIT {2}",
+        cursor, default_interval_minutes, limit
+    );
+    let stmt = db.prepare(&query);
+    let result = stmt.all().await?;
+    
+    let nodes: Vec<crate::models::ProxyNode> = result.results()?;
+    Ok(nodes)
+}
+
+pub async fn list_group_check_settings(db: &D1Database) -> Result<Vec<crate::models::GroupCheckSetting>> {
+    let stmt = db.prepare(
+        "SELECT COALESCE(g.name, '') AS group_name, s.check_interval_minutes, s.updated_at \
+         FROM group_check_settings s LEFT JOIN node_groups g ON g.id = s.group_id ORDER BY group_name"
+    );
+    let result = stmt.all().await?;
+    let settings: Vec<crate::models::GroupCheckSetting> = result.results()?;
+    Ok(settings)
+}
+
+// group_id 为空表示未分组的节点；可为空的列上 UNIQUE 不阻止重复的 NULL，先删除再写入
+pub async fn upsert_group_check_setting(db: &D1Database, group_id: Option<i64>, check_interval_minutes: i64) -> Result<()> {
+    let group_id = sql_id(group_id);
+    let statements = vec![
+        db.prepare(format!("DELETE FROM group_check_settings WHERE group_id IS {}", group_id)),
+        db.prepare(format!(
+            "INSERT INTO group_check_settings (group_id, check_interval_minutes, updated_at) VALUES ({}, {}, datetime('now'))",
+            group_id, check_interval_minutes
+        )),
+    ];
+    db.batch(statements).await?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+// 客户端配置导出的公共部分：代理名称去重、按节点分组整理代理
+
+use std::collections::HashSet;
+use crate::models::NodeGroup;
+
+// 汇总代理组名称
+pub const SELECT_GROUP: &str = "节点选择";
+pub const AUTO_GROUP: &str = "自动选择";
+// 自动选择的测速地址与间隔（秒）
+pub const URL_TEST_URL: &str = "http://www.gstatic.com/generate_204";
+pub const URL_TEST_INTERVAL: i64 = 300;
+
+// 客户端要求代理与代理组名称唯一，重名时追加序号
+pub struct UniqueNames {
+    used: HashSet<String>,
+}
+
+impl UniqueNames {
+    pub fn new(reserved: &[&str]) -> Self {
+        Self { used: reserved.iter().map(|s| s.to_string()).collect() }
+    }
+
+    pub fn claim(&mut self, name: &str) -> String {
+        let base = if name.trim().is_empty() { "未命名" } else { name.trim() };
+        let mut candidate = base.to_string();
+        let mut index = 2;
+        while self.used.contains(&candidate) {
+            candidate = format!("{} {}", base, index);
+            index += 1;
+        }
+        self.used.insert(candidate.clone());
+        candidate
+    }
+
+    pub fn release(&mut self, name: &str) {
+        self.used.remove(name);
+    }
+}
+
+// 已导出代理的分组情况
+#[derive(Default)]
+pub struct ProxyLayout {
+    // 全部代理名，保持节点顺序
+    pub all: Vec<String>,
+    // (分组名, 组内代理名)
+    pub groups: Vec<(String, Vec<String>)>,
+    // 未分组的代理名
+    pub ungrouped: Vec<String>,
+}
+
+impl ProxyLayout {
+    pub fn add(&mut self, group_name: Option<&str>, name: String) {
+        self.all.push(name.clone());
+        match group_name.filter(|g| !g.is_empty()) {
+            None => self.ungrouped.push(name),
+            Some(group_name) => match self.groups.iter_mut().find(|(g, _)| g == group_name) {
+                Some((_, list)) => list.push(name),
+                None => self.groups.push((group_name.to_string(), vec![name])),
+            },
+        }
+    }
+
+    // 分组按分组排序值排列，未登记的分组排在最后；分组名同样参与去重
+    pub fn finish(mut self, node_groups: &[NodeGroup], names: &mut UniqueNames) -> Self {
+        let position = |group_name: &str| node_groups.iter().position(|g| g.name == group_name).unwrap_or(usize::MAX);
+        self.groups.sort_by(|(a, _), (b, _)| position(a).cmp(&position(b)).then_with(|| a.cmp(b)));
+        for (group_name, _) in self.groups.iter_mut() {
+            *group_name = names.claim(group_name);
+        }
+        self
+    }
+
+    // 节点选择组的成员：自动选择、各分组、未分组的代理，最后是直连
+    pub fn selector_members(&self, direct: &str) -> Vec<String> {
+        let mut members = Vec::new();
+        if !self.all.is_empty() {
+            members.push(AUTO_GROUP.to_string());
+        }
+        members.extend(self.groups.iter().map(|(g, _)| g.clone()));
+        members.extend(self.ungrouped.iter().cloned());
+        members.push(direct.to_string());
+        members
+    }
+}
@@ -1,9 +1,19 @@
 mod auth;
+mod checker;
+mod clash;
 mod db;
+mod export;
 mod handlers;
 mod jwt;
 mod models;
+mod node_list;
+mod protocol;
+mod region;
+mod scheduler;
+mod share_link;
+mod singbox;
 mod utils;
+mod xray;
 
 use worker::*;
 
@@ -31,21 +41,57 @@ async fn main(req: Request, env: Env, _ctx: Context) -> Result<Response> {
         .put_async("/api/proxy/nodes/:id", handlers::proxy::update_node)
         .delete_async("/api/proxy/nodes/:id", handlers::proxy::delete_node)
         .post_async("/api/proxy/nodes/:id/check", handlers::proxy::check_node)
+        .get_async("/api/proxy/nodes/:id/stats", handlers::proxy::get_node_stats)
+        .get_async("/api/proxy/nodes/:id/link", handlers::proxy::get_node_link)
         .post_async("/api/proxy/nodes/check-all", handlers::proxy::check_all_nodes)
         .post_async("/api/proxy/import", handlers::proxy::import_subscription)
         .post_async("/api/proxy/nodes/batch-delete", handlers::proxy::batch_delete_nodes)
+        .post_async("/api/proxy/nodes/batch-update", handlers::proxy::batch_update_nodes)
+        .post_async("/api/proxy/nodes/migrate-config", handlers::proxy::migrate_node_configs)
+        .post_async("/api/proxy/nodes/move", handlers::proxy::move_nodes)
+        .post_async("/api/proxy/nodes/reorder", handlers::proxy::reorder_nodes)
+        .put_async("/api/proxy/nodes/:id/enabled", handlers::proxy::set_node_enabled)
+        .get_async("/api/proxy/groups", handlers::proxy::list_groups)
+        .post_async("/api/proxy/groups", handlers::proxy::create_group)
+        .put_async("/api/proxy/groups/:id", handlers::proxy::update_group)
+        .delete_async("/api/proxy/groups/:id", handlers::proxy::delete_group)
+        .get_async("/api/proxy/export", handlers::proxy::export_nodes)
         .get_async("/api/proxy/subscriptions", handlers::proxy::list_subscription_info)
+        .post_async("/api/proxy/subscriptions/:group/refresh", handlers::proxy::refresh_subscription_group)
+        .put_async("/api/proxy/subscriptions/:group/settings", handlers::proxy::update_subscription_setting)
+        .get_async("/api/proxy/check-settings", handlers::proxy::list_check_settings)
+        .put_async("/api/proxy/check-settings", handlers::proxy::update_check_setting)
         .options("/api/*path", |_, _| {
             Response::empty()
                 .map(|r| r.with_headers(utils::cors_headers()))
         })
         .run(req, env)
         .await
-        .map(|res| {
-            let mut headers = Headers::new();
+        .map(|mut res| {
+            // 在原有响应头上追加 CORS 头，保留 Content-Type 等自定义头
+            let headers = res.headers_mut();
             headers.set("Access-Control-Allow-Origin", "*").unwrap();
             headers.set("Access-Control-Allow-Methods", "GET, POST, PUT, DELETE, OPTIONS").unwrap();
             headers.set("Access-Control-Allow-Headers", "Content-Type, Authorization").unwrap();
-            res.with_headers(headers)
+            headers.set("Access-Control-Expose-Headers", "X-Skipped-Nodes, X-Unsupported-Nodes").unwrap();
+            res
         })
 }
+
+// 定时触发：后台检测节点可用性、刷新订阅
+#[event(scheduled)]
+async fn scheduled(_event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
+    console_error_panic_hook::set_once();
+
+    if let Err(e) = scheduler::run_health_checks(&env).await {
+        console_error!("scheduled health check failed: {}", e);
+    }
+
+    if let Err(e) = scheduler::refresh_due_subscriptions(&env).await {
+        console_error!("scheduled subscription refresh failed: {}", e);
+    }
+
+    if let Err(e) = scheduler::downsample_check_history(&env).await {
+        console_error!("check history downsampling failed: {}", e);
+    }
+}
@@ -88,12 +88,32 @@ pub struct ProxyNode {
     pub username: Option<String>,
     pub password: Option<String>,
     pub extra_config: Option<String>,
+    // 所在分组，为空表示未分组；group_name 由分组表关联得到
+    #[serde(default)]
+    pub group_id: Option<i64>,
     pub group_name: Option<String>,
     pub status: String,
     pub latency: Option<i64>,
     pub last_check_at: Option<String>,
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+    // 订阅刷新时上游已不再提供该节点的时间
+    #[serde(default)]
+    pub stale_at: Option<String>,
+    #[serde(default, deserialize_with = "string_list_from_json")]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub region: Option<String>,
+    // 禁用的节点不参与检测与导出
+    #[serde(default = "default_true", deserialize_with = "enabled_from_int")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub sort_order: i64,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub uptime_24h: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -105,7 +125,14 @@ pub struct CreateProxyNodeRequest {
     pub username: Option<String>,
     pub password: Option<String>,
     pub extra_config: Option<String>,
+    // 必须是已存在的分组，为空表示未分组
     pub group_name: Option<String>,
+    // 未提供时为空
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    // 未提供时从名称识别，空字符串表示不设置地区
+    #[serde(default)]
+    pub region: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -117,7 +144,14 @@ pub struct UpdateProxyNodeRequest {
     pub username: Option<String>,
     pub password: Option<String>,
     pub extra_config: Option<String>,
+    // 必须是已存在的分组，空字符串表示移出分组，未提供时保持不变
     pub group_name: Option<String>,
+    // 未提供时保持不变
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    // 未提供时保持不变，空字符串表示清除地区
+    #[serde(default)]
+    pub region: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -128,6 +162,71 @@ pub struct ProxyCheckResult {
     pub message: String,
 }
 
+// 节点筛选条件
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ProxyNodeFilter {
+    pub group_name: Option<String>,
+    pub protocol: Option<String>,
+    pub status: Option<String>,
+    pub search: Option<String>,
+    pub max_latency: Option<i64>,
+    pub ids: Option<Vec<i64>>,
+    pub region: Option<String>,
+    // 同时包含所有标签的节点
+    pub tags: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+impl ProxyNodeFilter {
+    // 没有任何生效的筛选条件，即匹配全部节点（空白搜索词与空标签列表不生效）
+    pub fn is_empty(&self) -> bool {
+        self.group_name.is_none()
+            && self.protocol.is_none()
+            && self.status.is_none()
+            && self.search.as_deref().is_none_or(|s| s.trim().is_empty())
+            && self.max_latency.is_none()
+            && self.ids.is_none()
+            && self.region.is_none()
+            && self.tags.as_ref().is_none_or(|tags| tags.is_empty())
+            && self.enabled.is_none()
+    }
+}
+
+// 分面统计项
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProxyNodeFacets {
+    pub protocol: Vec<FacetCount>,
+    pub status: Vec<FacetCount>,
+    pub group_name: Vec<FacetCount>,
+    pub region: Vec<FacetCount>,
+    pub tags: Vec<FacetCount>,
+}
+
+// 节点分页列表
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProxyNodeList {
+    pub items: Vec<ProxyNode>,
+    pub total: i64,
+    pub next_cursor: Option<String>,
+    pub facets: ProxyNodeFacets,
+}
+
+// 批量检测结果，partial 为 true 时 skipped_ids 中的节点因超出时间预算未检测
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchCheckResult {
+    pub total: usize,
+    pub checked: usize,
+    pub partial: bool,
+    pub skipped_ids: Vec<i64>,
+    pub results: Vec<ProxyCheckResult>,
+}
+
 // 订阅信息模型
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SubscriptionInfo {
@@ -140,4 +239,169 @@ pub struct SubscriptionInfo {
     pub expire_timestamp: Option<i64>,
     pub last_update_at: Option<String>,
     pub created_at: String,
+    // 定时刷新设置
+    #[serde(default, deserialize_with = "bool_from_int")]
+    pub refresh_enabled: bool,
+    #[serde(default)]
+    pub refresh_interval_minutes: i64,
+    // 最近一次刷新结果
+    #[serde(default)]
+    pub last_refresh_at: Option<String>,
+    #[serde(default)]
+    pub last_refresh_status: Option<String>,
+    #[serde(default)]
+    pub last_refresh_added: Option<i64>,
+    #[serde(default)]
+    pub last_refresh_updated: Option<i64>,
+    #[serde(default)]
+    pub last_refresh_vanished: Option<i64>,
+    #[serde(default)]
+    pub last_refresh_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSubscriptionSettingRequest {
+    pub refresh_enabled: bool,
+    pub refresh_interval_minutes: i64,
+}
+
+// 节点分组
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NodeGroup {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    pub sort_order: i64,
+    #[serde(default)]
+    pub node_count: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateNodeGroupRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    #[serde(default)]
+    pub sort_order: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateNodeGroupRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    #[serde(default)]
+    pub sort_order: i64,
+}
+
+// 在分组间移动节点，group_id 为空表示移出分组
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveNodesRequest {
+    pub ids: Vec<i64>,
+    pub group_id: Option<i64>,
+}
+
+// 启用或禁用节点
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetNodeEnabledRequest {
+    pub enabled: bool,
+}
+
+// 分组内节点重排，ids 为拖动后的顺序，group_name 为空表示未分组
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReorderNodesRequest {
+    pub group_name: Option<String>,
+    pub ids: Vec<i64>,
+}
+
+// 分组定时检测设置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupCheckSetting {
+    pub group_name: String,
+    pub check_interval_minutes: i64,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateGroupCheckSettingRequest {
+    pub group_name: String,
+    pub check_interval_minutes: i64,
+}
+
+// 单次检测记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProxyNodeCheck {
+    pub status: String,
+    pub latency: Option<i64>,
+    pub message: Option<String>,
+    pub checked_at: String,
+}
+
+// 节点详情：节点字段以及从协议配置中解析出的传输与 TLS 参数
+#[derive(Debug, Serialize)]
+pub struct ProxyNodeDetail {
+    #[serde(flatten)]
+    pub node: ProxyNode,
+    pub transport: Option<crate::protocol::Transport>,
+    pub tls: Option<crate::protocol::TlsOptions>,
+}
+
+// 节点可用性统计
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProxyNodeStats {
+    pub node_id: i64,
+    pub window_hours: i64,
+    pub total_checks: i64,
+    pub valid_checks: i64,
+    pub uptime: Option<f64>,
+    // 延迟统计只来自原始检测记录，窗口不超过原始记录的保留期
+    pub latency_window_hours: i64,
+    pub avg_latency: Option<i64>,
+    pub p50_latency: Option<i64>,
+    pub p95_latency: Option<i64>,
+    pub timeline: Vec<ProxyNodeCheck>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// D1 中布尔值以 0 / 1 存储
+fn bool_from_int<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Bool(b) => Ok(b),
+        serde_json::Value::Number(n) => Ok(n.as_f64().map(|v| v != 0.0).unwrap_or(false)),
+        _ => Ok(false),
+    }
+}
+
+// 节点启用状态为 NULL 时视为启用，与 SQL 中的 COALESCE(enabled, 1) 一致
+fn enabled_from_int<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Null => Ok(true),
+        value => bool_from_int(value).map_err(serde::de::Error::custom),
+    }
+}
+
+// 以 JSON 字符串存储的字符串数组，如 tags 列
+fn string_list_from_json<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => Ok(serde_json::from_str(&s).unwrap_or_default()),
+        serde_json::Value::Array(items) => Ok(items.into_iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect()),
+        _ => Ok(Vec::new()),
+    }
 }
@@ -0,0 +1,497 @@
+// iOS 客户端节点列表：Surge [Proxy] 行、Quantumult X server_remote 行、Loon [Proxy] 行
+// 目标客户端无法表示的协议或参数返回 Err，由调用方记为跳过，不输出残缺的行
+
+use crate::export::UniqueNames;
+use crate::models::ProxyNode;
+use crate::protocol::{self, NodeConfig, Protocol, SsConfig, TlsOptions, Transport};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeListFormat {
+    Surge,
+    QuantumultX,
+    Loon,
+}
+
+impl NodeListFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "surge" => Some(NodeListFormat::Surge),
+            "quanx" | "quantumultx" => Some(NodeListFormat::QuantumultX),
+            "loon" => Some(NodeListFormat::Loon),
+            _ => None,
+        }
+    }
+
+    fn client(&self) -> &'static str {
+        match self {
+            NodeListFormat::Surge => "Surge",
+            NodeListFormat::QuantumultX => "Quantumult X",
+            NodeListFormat::Loon => "Loon",
+        }
+    }
+}
+
+// 生成节点列表，每行一个节点，返回文本与跳过的节点 id
+pub fn render(nodes: &[ProxyNode], format: NodeListFormat) -> (String, Vec<i64>) {
+    let mut names = UniqueNames::new(&[]);
+    let mut lines = Vec::with_capacity(nodes.len());
+    let mut skipped = Vec::new();
+
+    for node in nodes {
+        let name = names.claim(&clean_name(&node.name));
+        match node_line(node, &name, format) {
+            Ok(line) => lines.push(line),
+            Err(_) => {
+                names.release(&name);
+                skipped.push(node.id);
+            }
+        }
+    }
+
+    (lines.join("\n"), skipped)
+}
+
+pub fn node_line(node: &ProxyNode, name: &str, format: NodeListFormat) -> Result<String, String> {
+    let (node_protocol, config) = protocol::node_config(node)?;
+    let line = match format {
+        NodeListFormat::Surge => surge_line(node, name, node_protocol, &config),
+        NodeListFormat::QuantumultX => quanx_line(node, name, node_protocol, &config),
+        NodeListFormat::Loon => loon_line(node, name, node_protocol, &config),
+    };
+    line.map_err(|e| format!("{} {}", format.client(), e))
+}
+
+// 名称中的逗号与等号会破坏行结构
+fn clean_name(name: &str) -> String {
+    name.replace([',', '=', '\n', '\r'], " ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// IPv6 地址不带方括号
+fn bare_host(address: &str) -> &str {
+    address.trim_start_matches('[').trim_end_matches(']')
+}
+
+// Surge：Name = type, server, port, key=value, ...
+fn surge_line(node: &ProxyNode, name: &str, node_protocol: Protocol, config: &NodeConfig) -> Result<String, String> {
+    let kind = match node_protocol {
+        Protocol::Tuic => "tuic-v5",
+        other => other.as_str(),
+    };
+    let mut parts = vec![
+        kind.to_string(),
+        bare_host(&node.address).to_string(),
+        node.port.to_string(),
+    ];
+    let username = node.username.as_deref().filter(|u| !u.is_empty());
+    let password = node.password.as_deref().filter(|p| !p.is_empty());
+
+    match (node_protocol, config) {
+        (Protocol::Http | Protocol::Https | Protocol::Socks5, _) => {
+            if let Some(username) = username {
+                parts.push(format!("username={}", surge_value(username)?));
+            }
+            if let Some(password) = password {
+                parts.push(format!("password={}", surge_value(password)?));
+            }
+        }
+        (Protocol::Ss, NodeConfig::Ss(c)) => {
+            parts.push(format!("encrypt-method={}", c.cipher));
+            parts.push(format!("password={}", surge_value(password.unwrap_or(""))?));
+            if let Some((obfs, host)) = obfs_plugin(c)? {
+                parts.push(format!("obfs={}", obfs));
+                if let Some(host) = host {
+                    parts.push(format!("obfs-host={}", surge_value(&host)?));
+                }
+            }
+            parts.push("udp-relay=true".to_string());
+        }
+        (Protocol::Vmess, NodeConfig::Vmess(c)) => {
+            parts.push(format!("username={}", c.uuid));
+            surge_transport(&mut parts, &c.transport())?;
+            let tls = c.tls_options();
+            if tls.enabled {
+                parts.push("tls=true".to_string());
+                surge_tls(&mut parts, &tls);
+            }
+            if c.alter_id == 0 {
+                parts.push("vmess-aead=true".to_string());
+            }
+        }
+        (Protocol::Trojan, NodeConfig::Trojan(c)) => {
+            parts.push(format!("password={}", surge_value(password.unwrap_or(""))?));
+            surge_transport(&mut parts, &c.transport())?;
+            surge_tls(&mut parts, &c.tls_options());
+        }
+        (Protocol::Vless | Protocol::Ssr, _) => return Err(format!("不支持 {} 协议", node_protocol)),
+        (Protocol::Hysteria2, NodeConfig::Hysteria2(c)) => {
+            if c.obfs.as_deref().is_some_and(|o| !o.is_empty()) {
+                return Err("不支持 hysteria2 混淆".to_string());
+            }
+            parts.push(format!("password={}", surge_value(password.unwrap_or(""))?));
+            if let Some(down) = c.down_mbps() {
+                parts.push(format!("download-bandwidth={}", down));
+            }
+            surge_tls(&mut parts, &c.tls_options());
+        }
+        (Protocol::Tuic, NodeConfig::Tuic(c)) => {
+            parts.push(format!("password={}", surge_value(password.unwrap_or(""))?));
+            parts.push(format!("uuid={}", c.uuid));
+            if !c.alpn.is_empty() {
+                parts.push(format!("alpn={}", surge_value(&c.alpn.join(","))?));
+            }
+            surge_tls(&mut parts, &c.tls_options());
+        }
+        _ => return Err(format!("{} 节点配置与协议不符", node_protocol)),
+    }
+
+    Ok(format!("{} = {}", name, parts.join(", ")))
+}
+
+fn surge_transport(parts: &mut Vec<String>, transport: &Transport) -> Result<(), String> {
+    match transport.network.as_str() {
+        "" | "tcp" if transport.header_type.is_none() => Ok(()),
+        "ws" => {
+            parts.push("ws=true".to_string());
+            if let Some(path) = &transport.path {
+                parts.push(format!("ws-path={}", surge_value(path)?));
+            }
+            if let Some(host) = &transport.host {
+                parts.push(format!("ws-headers=Host:{}", surge_value(host)?));
+            }
+            Ok(())
+        }
+        other => Err(format!("不支持 {} 传输", transport.header_type.as_deref().unwrap_or(other))),
+    }
+}
+
+fn surge_tls(parts: &mut Vec<String>, tls: &TlsOptions) {
+    if let Some(sni) = &tls.sni {
+        parts.push(format!("sni={}", sni));
+    }
+    if tls.allow_insecure {
+        parts.push("skip-cert-verify=true".to_string());
+    }
+}
+
+// 含逗号或首尾空白的值需要加引号，值本身不能含引号
+fn surge_value(value: &str) -> Result<String, String> {
+    if value.contains(['"', '\n', '\r']) {
+        return Err("参数值不能包含引号或换行".to_string());
+    }
+    if value.contains(',') || value.trim() != value {
+        Ok(format!("\"{}\"", value))
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+// Quantumult X：type=server:port, key=value, ..., tag=Name
+fn quanx_line(node: &ProxyNode, name: &str, node_protocol: Protocol, config: &NodeConfig) -> Result<String, String> {
+    let kind = match node_protocol {
+        Protocol::Http | Protocol::Https => "http",
+        Protocol::Socks5 => "socks5",
+        Protocol::Ss | Protocol::Ssr => "shadowsocks",
+        Protocol::Vmess => "vmess",
+        Protocol::Trojan => "trojan",
+        Protocol::Vless => "vless",
+        Protocol::Hysteria2 | Protocol::Tuic => return Err(format!("不支持 {} 协议", node_protocol)),
+    };
+    let mut parts = vec![format!("{}={}:{}", kind, node.address, node.port)];
+    let username = node.username.as_deref().filter(|u| !u.is_empty());
+    let password = node.password.as_deref().filter(|p| !p.is_empty());
+
+    match (node_protocol, config) {
+        (Protocol::Http | Protocol::Https | Protocol::Socks5, _) => {
+            if let Some(username) = username {
+                parts.push(format!("username={}", quanx_value(username)?));
+            }
+            if let Some(password) = password {
+                parts.push(format!("password={}", quanx_value(password)?));
+            }
+            if node_protocol == Protocol::Https {
+                parts.push("over-tls=true".to_string());
+            }
+        }
+        (Protocol::Ss, NodeConfig::Ss(c)) => {
+            parts.push(format!("method={}", c.cipher));
+            parts.push(format!("password={}", quanx_value(password.unwrap_or(""))?));
+            quanx_ss_plugin(&mut parts, c)?;
+            parts.push("udp-relay=true".to_string());
+        }
+        (Protocol::Ssr, NodeConfig::Ssr(c)) => {
+            parts.push(format!("method={}", c.cipher));
+            parts.push(format!("password={}", quanx_value(password.unwrap_or(""))?));
+            parts.push(format!("ssr-protocol={}", c.protocol));
+            if let Some(param) = &c.protocol_param {
+                parts.push(format!("ssr-protocol-param={}", quanx_value(param)?));
+            }
+            parts.push(format!("obfs={}", c.obfs));
+            if let Some(param) = &c.obfs_param {
+                parts.push(format!("obfs-host={}", quanx_value(param)?));
+            }
+        }
+        (Protocol::Vmess, NodeConfig::Vmess(c)) => {
+            // Quantumult X 的 vmess 只支持这几种加密方式，auto 按 chacha20 处理
+            let method = match c.security.as_str() {
+                "auto" | "chacha20-poly1305" | "chacha20-ietf-poly1305" => "chacha20-ietf-poly1305",
+                "aes-128-gcm" => "aes-128-gcm",
+                "none" | "zero" => "none",
+                other => return Err(format!("不支持 vmess 加密方式 {}", other)),
+            };
+            parts.push(format!("method={}", method));
+            parts.push(format!("password={}", c.uuid));
+            let tls = c.tls_options();
+            quanx_transport(&mut parts, &c.transport(), &tls, "obfs=over-tls")?;
+            parts.push(format!("aead={}", c.alter_id == 0));
+        }
+        (Protocol::Trojan, NodeConfig::Trojan(c)) => {
+            parts.push(format!("password={}", quanx_value(password.unwrap_or(""))?));
+            quanx_transport(&mut parts, &c.transport(), &c.tls_options(), "over-tls=true")?;
+        }
+        (Protocol::Vless, NodeConfig::Vless(c)) => {
+            // Quantumult X 的 vless 不支持 REALITY 与 XTLS 流控
+            if c.is_reality() {
+                return Err("不支持 REALITY".to_string());
+            }
+            if let Some(flow) = &c.flow {
+                return Err(format!("不支持 {} 流控", flow));
+            }
+            parts.push("method=none".to_string());
+            parts.push(format!("password={}", c.uuid));
+            quanx_transport(&mut parts, &c.transport(), &c.tls_options(), "obfs=over-tls")?;
+        }
+        _ => return Err(format!("{} 节点配置与协议不符", node_protocol)),
+    }
+
+    parts.push(format!("tag={}", name));
+    Ok(parts.join(", "))
+}
+
+// tcp 上的 TLS 由 over_tls 参数表示（vmess 为 obfs=over-tls，trojan 为 over-tls=true），ws 上为 obfs=wss
+fn quanx_transport(parts: &mut Vec<String>, transport: &Transport, tls: &TlsOptions, over_tls: &str) -> Result<(), String> {
+    match (transport.network.as_str(), transport.header_type.as_deref()) {
+        ("" | "tcp", None) => {
+            if tls.enabled {
+                parts.push(over_tls.to_string());
+            }
+        }
+        ("" | "tcp", Some("http")) if !tls.enabled => {
+            parts.push("obfs=http".to_string());
+            quanx_obfs_target(parts, transport)?;
+        }
+        ("ws", _) => {
+            parts.push(format!("obfs={}", if tls.enabled { "wss" } else { "ws" }));
+            quanx_obfs_target(parts, transport)?;
+        }
+        (other, header_type) => return Err(format!("不支持 {} 传输", header_type.unwrap_or(other))),
+    }
+    if tls.enabled {
+        if let Some(sni) = &tls.sni {
+            parts.push(format!("tls-host={}", quanx_value(sni)?));
+        }
+        parts.push(format!("tls-verification={}", !tls.allow_insecure));
+    }
+    Ok(())
+}
+
+fn quanx_obfs_target(parts: &mut Vec<String>, transport: &Transport) -> Result<(), String> {
+    if let Some(host) = &transport.host {
+        parts.push(format!("obfs-host={}", quanx_value(host)?));
+    }
+    if let Some(path) = &transport.path {
+        parts.push(format!("obfs-uri={}", quanx_value(path)?));
+    }
+    Ok(())
+}
+
+// simple-obfs 对应 obfs=http/tls，v2ray-plugin 的 websocket 模式对应 obfs=ws/wss
+fn quanx_ss_plugin(parts: &mut Vec<String>, config: &SsConfig) -> Result<(), String> {
+    let Some((name, options)) = config.plugin_options() else {
+        return Ok(());
+    };
+    match name.as_str() {
+        "obfs-local" | "simple-obfs" | "obfs" => {
+            let (obfs, host) = obfs_plugin(config)?.unwrap_or(("http".to_string(), None));
+            parts.push(format!("obfs={}", obfs));
+            if let Some(host) = host {
+                parts.push(format!("obfs-host={}", quanx_value(&host)?));
+            }
+        }
+        "v2ray-plugin" if options.get("mode").is_none_or(|m| m == "websocket") => {
+            parts.push(format!("obfs={}", if options.contains_key("tls") { "wss" } else { "ws" }));
+            if let Some(host) = options.get("host") {
+                parts.push(format!("obfs-host={}", quanx_value(host)?));
+            }
+            if let Some(path) = options.get("path") {
+                parts.push(format!("obfs-uri={}", quanx_value(path)?));
+            }
+        }
+        other => return Err(format!("不支持 ss 插件 {}", other)),
+    }
+    Ok(())
+}
+
+// 值中不能有逗号，也没有引号转义
+fn quanx_value(value: &str) -> Result<String, String> {
+    if value.contains([',', '\n', '\r']) || value.trim() != value {
+        return Err("参数值不能包含逗号、换行或首尾空白".to_string());
+    }
+    Ok(value.to_string())
+}
+
+// Loon：Name = type,server,port,...，密码加引号
+fn loon_line(node: &ProxyNode, name: &str, node_protocol: Protocol, config: &NodeConfig) -> Result<String, String> {
+    let kind = match node_protocol {
+        Protocol::Ss => "Shadowsocks",
+        Protocol::Ssr => "ShadowsocksR",
+        Protocol::Vless => "VLESS",
+        Protocol::Hysteria2 => "Hysteria2",
+        Protocol::Tuic => return Err("不支持 tuic 协议".to_string()),
+        other => other.as_str(),
+    };
+    let mut parts = vec![kind.to_string(), bare_host(&node.address).to_string(), node.port.to_string()];
+    let username = node.username.as_deref().filter(|u| !u.is_empty());
+    let password = node.password.as_deref().filter(|p| !p.is_empty());
+
+    match (node_protocol, config) {
+        (Protocol::Http | Protocol::Https | Protocol::Socks5, _) => match (username, password) {
+            (Some(username), password) => {
+                parts.push(loon_value(username)?);
+                parts.push(loon_quoted(password.unwrap_or(""))?);
+            }
+            (None, Some(_)) => return Err("不支持只有密码的认证".to_string()),
+            (None, None) => {}
+        },
+        (Protocol::Ss, NodeConfig::Ss(c)) => {
+            parts.push(c.cipher.clone());
+            parts.push(loon_quoted(password.unwrap_or(""))?);
+            if let Some((obfs, host)) = obfs_plugin(c)? {
+                parts.push(format!("obfs-name={}", obfs));
+                if let Some(host) = host {
+                    parts.push(format!("obfs-host={}", loon_value(&host)?));
+                }
+            }
+            parts.push("udp=true".to_string());
+        }
+        (Protocol::Ssr, NodeConfig::Ssr(c)) => {
+            parts.push(c.cipher.clone());
+            parts.push(loon_quoted(password.unwrap_or(""))?);
+            parts.push(format!("protocol={}", c.protocol));
+            if let Some(param) = &c.protocol_param {
+                parts.push(format!("protocol-param={}", loon_value(param)?));
+            }
+            parts.push(format!("obfs={}", c.obfs));
+            if let Some(param) = &c.obfs_param {
+                parts.push(format!("obfs-param={}", loon_value(param)?));
+            }
+        }
+        (Protocol::Vmess, NodeConfig::Vmess(c)) => {
+            parts.push(c.security.clone());
+            parts.push(loon_quoted(&c.uuid)?);
+            loon_transport(&mut parts, &c.transport())?;
+            let tls = c.tls_options();
+            if tls.enabled {
+                parts.push("over-tls=true".to_string());
+                loon_tls(&mut parts, &tls)?;
+            }
+            parts.push(format!("alterId={}", c.alter_id));
+        }
+        (Protocol::Trojan, NodeConfig::Trojan(c)) => {
+            parts.push(loon_quoted(password.unwrap_or(""))?);
+            loon_transport(&mut parts, &c.transport())?;
+            parts.push("over-tls=true".to_string());
+            loon_tls(&mut parts, &c.tls_options())?;
+        }
+        (Protocol::Vless, NodeConfig::Vless(c)) => {
+            parts.push(loon_quoted(&c.uuid)?);
+            loon_transport(&mut parts, &c.transport())?;
+            let tls = c.tls_options();
+            if tls.enabled {
+                parts.push("over-tls=true".to_string());
+                loon_tls(&mut parts, &tls)?;
+            }
+            if let Some(flow) = &c.flow {
+                parts.push(format!("flow={}", loon_value(flow)?));
+            }
+            if c.is_reality() {
+                parts.push(format!("public-key={}", loon_quoted(c.public_key.as_deref().unwrap_or(""))?));
+                if let Some(short_id) = &c.short_id {
+                    parts.push(format!("short-id={}", loon_value(short_id)?));
+                }
+            }
+        }
+        (Protocol::Hysteria2, NodeConfig::Hysteria2(c)) => {
+            parts.push(loon_quoted(password.unwrap_or(""))?);
+            parts.push("udp=true".to_string());
+            loon_tls(&mut parts, &c.tls_options())?;
+            if c.obfs.as_deref().is_some_and(|o| !o.is_empty()) {
+                parts.push(format!("salamander-password={}", loon_quoted(c.obfs_password.as_deref().unwrap_or(""))?));
+            }
+            if let Some(down) = c.down_mbps() {
+                parts.push(format!("download-bandwidth={}", down));
+            }
+        }
+        _ => return Err(format!("{} 节点配置与协议不符", node_protocol)),
+    }
+
+    Ok(format!("{} = {}", name, parts.join(",")))
+}
+
+fn loon_transport(parts: &mut Vec<String>, transport: &Transport) -> Result<(), String> {
+    let transport_name = match (transport.network.as_str(), transport.header_type.as_deref()) {
+        ("" | "tcp", None) => {
+            parts.push("transport=tcp".to_string());
+            return Ok(());
+        }
+        ("" | "tcp", Some("http")) => "http",
+        ("ws", _) => "ws",
+        (other, header_type) => return Err(format!("不支持 {} 传输", header_type.unwrap_or(other))),
+    };
+    parts.push(format!("transport={}", transport_name));
+    if let Some(path) = &transport.path {
+        parts.push(format!("path={}", loon_value(path)?));
+    }
+    if let Some(host) = &transport.host {
+        parts.push(format!("host={}", loon_value(host)?));
+    }
+    Ok(())
+}
+
+fn loon_tls(parts: &mut Vec<String>, tls: &TlsOptions) -> Result<(), String> {
+    if let Some(sni) = &tls.sni {
+        parts.push(format!("sni={}", loon_value(sni)?));
+    }
+    parts.push(format!("skip-cert-verify={}", tls.allow_insecure));
+    Ok(())
+}
+
+fn loon_value(value: &str) -> Result<String, String> {
+    if value.contains([',', '"', '\n', '\r']) {
+        return Err("参数值不能包含逗号、引号或换行".to_string());
+    }
+    Ok(value.to_string())
+}
+
+fn loon_quoted(value: &str) -> Result<String, String> {
+    if value.contains(['"', '\n', '\r']) {
+        return Err("参数值不能包含引号或换行".to_string());
+    }
+    Ok(format!("\"{}\"", value))
+}
+
+// simple-obfs 插件的混淆方式与 Host，其他插件无法表示
+fn obfs_plugin(config: &SsConfig) -> Result<Option<(String, Option<String>)>, String> {
+    let Some((name, options)) = config.plugin_options() else {
+        return Ok(None);
+    };
+    match name.as_str() {
+        "obfs-local" | "simple-obfs" | "obfs" => {
+            let obfs = options.get("obfs").cloned().unwrap_or_else(|| "http".to_string());
+            if obfs != "http" && obfs != "tls" {
+                return Err(format!("不支持 obfs={}", obfs));
+            }
+            Ok(Some((obfs, options.get("obfs-host").cloned())))
+        }
+        other => Err(format!("不支持 ss 插件 {}", other)),
+    }
+}
@@ -0,0 +1,1167 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Digest};
+use worker::Url;
+use crate::models::ProxyNode;
+
+// 支持的代理协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Http,
+    Https,
+    Socks5,
+    Ss,
+    Ssr,
+    Vmess,
+    Vless,
+    Trojan,
+    Hysteria2,
+    Tuic,
+}
+
+impl Protocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Http => "http",
+            Protocol::Https => "https",
+            Protocol::Socks5 => "socks5",
+            Protocol::Ss => "ss",
+            Protocol::Ssr => "ssr",
+            Protocol::Vmess => "vmess",
+            Protocol::Vless => "vless",
+            Protocol::Trojan => "trojan",
+            Protocol::Hysteria2 => "hysteria2",
+            Protocol::Tuic => "tuic",
+        }
+    }
+
+    // 解析协议名，兼容常见别名
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "http" => Some(Protocol::Http),
+            "https" => Some(Protocol::Https),
+            "socks5" | "socks" => Some(Protocol::Socks5),
+            "ss" | "shadowsocks" => Some(Protocol::Ss),
+            "ssr" | "shadowsocksr" => Some(Protocol::Ssr),
+            "vmess" => Some(Protocol::Vmess),
+            "vless" => Some(Protocol::Vless),
+            "trojan" => Some(Protocol::Trojan),
+            "hysteria2" | "hy2" => Some(Protocol::Hysteria2),
+            "tuic" => Some(Protocol::Tuic),
+            _ => None,
+        }
+    }
+
+    // 基于 QUIC（UDP）的协议，Worker 只能建立 TCP 连接，无法检测可用性
+    pub fn is_udp(&self) -> bool {
+        matches!(self, Protocol::Hysteria2 | Protocol::Tuic)
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// VMess 配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VmessConfig {
+    pub uuid: String,
+    pub alter_id: i64,
+    pub security: String,
+    // tcp / kcp / ws / http / h2 / quic / grpc / httpupgrade
+    pub network: String,
+    pub tls: bool,
+    pub host: Option<String>,
+    pub path: Option<String>,
+    // grpc 服务名，v2rayN 格式中保存在 path 中
+    #[serde(alias = "serviceName")]
+    pub service_name: Option<String>,
+    // 伪装类型，v2rayN 格式中的 type
+    #[serde(alias = "type")]
+    pub header_type: Option<String>,
+    // 其他参数（sni、alpn、fp 等），原样保留
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl Default for VmessConfig {
+    fn default() -> Self {
+        Self {
+            uuid: String::new(),
+            alter_id: 0,
+            security: "auto".to_string(),
+            network: "tcp".to_string(),
+            tls: false,
+            host: None,
+            path: None,
+            service_name: None,
+            header_type: None,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+// Trojan 配置，传输参数与分享链接中的 type / host / path / serviceName / headerType 对应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrojanConfig {
+    pub sni: Option<String>,
+    pub alpn: Vec<String>,
+    pub allow_insecure: bool,
+    // tcp / ws / grpc 等
+    #[serde(alias = "type")]
+    pub network: String,
+    pub host: Option<String>,
+    pub path: Option<String>,
+    #[serde(alias = "serviceName")]
+    pub service_name: Option<String>,
+    #[serde(alias = "headerType")]
+    pub header_type: Option<String>,
+    // 其他参数（fp 等），原样保留
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl Default for TrojanConfig {
+    fn default() -> Self {
+        Self {
+            sni: None,
+            alpn: Vec::new(),
+            allow_insecure: false,
+            network: "tcp".to_string(),
+            host: None,
+            path: None,
+            service_name: None,
+            header_type: None,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+// VLESS 配置，security 为 none / tls / reality
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VlessConfig {
+    pub uuid: String,
+    // 流控，如 xtls-rprx-vision
+    pub flow: Option<String>,
+    pub security: String,
+    pub sni: Option<String>,
+    pub alpn: Vec<String>,
+    // uTLS 指纹，如 chrome
+    pub fingerprint: Option<String>,
+    pub allow_insecure: bool,
+    // REALITY 公钥、short id 与 spiderX
+    pub public_key: Option<String>,
+    pub short_id: Option<String>,
+    pub spider_x: Option<String>,
+    // tcp / ws / grpc / h2 / httpupgrade / xhttp 等
+    pub network: String,
+    pub host: Option<String>,
+    pub path: Option<String>,
+    pub service_name: Option<String>,
+    pub header_type: Option<String>,
+    // 其他参数（encryption、mode 等），原样保留
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl Default for VlessConfig {
+    fn default() -> Self {
+        Self {
+            uuid: String::new(),
+            flow: None,
+            security: "none".to_string(),
+            sni: None,
+            alpn: Vec::new(),
+            fingerprint: None,
+            allow_insecure: false,
+            public_key: None,
+            short_id: None,
+            spider_x: None,
+            network: "tcp".to_string(),
+            host: None,
+            path: None,
+            service_name: None,
+            header_type: None,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+// Hysteria2 配置，认证密码存放在 password 列
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hysteria2Config {
+    pub sni: Option<String>,
+    pub alpn: Vec<String>,
+    pub allow_insecure: bool,
+    // 混淆类型（salamander）与混淆密码
+    pub obfs: Option<String>,
+    pub obfs_password: Option<String>,
+    // 上下行带宽提示，如 "100 Mbps"
+    pub up: Option<String>,
+    pub down: Option<String>,
+    // 其他参数（pinSHA256 等），原样保留
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+// TUIC v5 配置，uuid 同步到 username 列，密码存放在 password 列
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TuicConfig {
+    pub uuid: String,
+    pub sni: Option<String>,
+    pub alpn: Vec<String>,
+    pub allow_insecure: bool,
+    // 拥塞控制：cubic / new_reno / bbr
+    pub congestion_control: Option<String>,
+    // UDP 转发模式：native / quic
+    pub udp_relay_mode: Option<String>,
+    // 其他参数（disable_sni 等），原样保留
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+// Shadowsocks 配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SsConfig {
+    pub cipher: String,
+    pub plugin: Option<String>,
+}
+
+// ShadowsocksR 配置，密码存放在 password 列
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SsrConfig {
+    pub cipher: String,
+    // 协议插件及参数，如 auth_aes128_md5
+    pub protocol: String,
+    pub protocol_param: Option<String>,
+    // 混淆插件及参数，如 tls1.2_ticket_auth
+    pub obfs: String,
+    pub obfs_param: Option<String>,
+}
+
+// 按协议区分的节点配置，序列化后存放在 extra_config 中
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeConfig {
+    // http / https / socks5 没有额外配置
+    Plain,
+    Ss(SsConfig),
+    Ssr(SsrConfig),
+    Vmess(VmessConfig),
+    Vless(VlessConfig),
+    Trojan(TrojanConfig),
+    Hysteria2(Hysteria2Config),
+    Tuic(TuicConfig),
+}
+
+const VMESS_SECURITIES: &[&str] = &["auto", "aes-128-gcm", "chacha20-poly1305", "none", "zero"];
+const VMESS_NETWORKS: &[&str] = &["tcp", "kcp", "ws", "http", "h2", "quic", "grpc", "httpupgrade"];
+const VLESS_SECURITIES: &[&str] = &["none", "tls", "reality"];
+const VLESS_NETWORKS: &[&str] = &["tcp", "kcp", "ws", "http", "h2", "quic", "grpc", "httpupgrade", "xhttp", "splithttp"];
+const TUIC_CONGESTION_CONTROLS: &[&str] = &["cubic", "new_reno", "bbr"];
+const TUIC_UDP_RELAY_MODES: &[&str] = &["native", "quic"];
+const SSR_CIPHERS: &[&str] = &[
+    "none", "table", "rc4", "rc4-md5", "rc4-md5-6",
+    "aes-128-cfb", "aes-192-cfb", "aes-256-cfb",
+    "aes-128-ctr", "aes-192-ctr", "aes-256-ctr",
+    "bf-cfb", "camellia-128-cfb", "camellia-192-cfb", "camellia-256-cfb",
+    "salsa20", "chacha20", "chacha20-ietf",
+];
+const SSR_PROTOCOLS: &[&str] = &[
+    "origin", "verify_deflate", "auth_sha1_v4", "auth_aes128_md5", "auth_aes128_sha1", "auth_chain_a", "auth_chain_b",
+];
+const SSR_OBFS: &[&str] = &["plain", "http_simple", "http_post", "random_head", "tls1.2_ticket_auth", "tls1.2_ticket_fastauth"];
+const SS_CIPHERS: &[&str] = &[
+    "aes-128-gcm", "aes-192-gcm", "aes-256-gcm",
+    "chacha20-ietf-poly1305", "xchacha20-ietf-poly1305",
+    "2022-blake3-aes-128-gcm", "2022-blake3-aes-256-gcm", "2022-blake3-chacha20-poly1305",
+    "aes-128-cfb", "aes-192-cfb", "aes-256-cfb",
+    "aes-128-ctr", "aes-192-ctr", "aes-256-ctr",
+    "chacha20-ietf", "chacha20", "rc4-md5", "none", "plain",
+];
+
+impl NodeConfig {
+    // 解析存储的 extra_config：优先按类型化 JSON 解析，
+    // 兼容旧数据中的 vmess 原始 JSON 与 trojan:// / ss:// 分享链接
+    pub fn from_stored(protocol: Protocol, extra_config: Option<&str>, username: Option<&str>) -> Result<NodeConfig, String> {
+        let extra = extra_config.unwrap_or("").trim();
+        let username = username.unwrap_or("").trim();
+
+        match protocol {
+            Protocol::Http | Protocol::Https | Protocol::Socks5 => Ok(NodeConfig::Plain),
+            Protocol::Vmess => {
+                let mut config = if extra.is_empty() {
+                    VmessConfig::default()
+                } else if let Some(encoded) = extra.strip_prefix("vmess://") {
+                    let decoded = crate::utils::base64_decode(encoded)
+                        .ok_or_else(|| "vmess 链接不是有效的 Base64".to_string())?;
+                    vmess_from_legacy_json(&parse_json_object(&decoded)?)
+                } else {
+                    let json = parse_json_object(extra)?;
+                    if json.contains_key("uuid") {
+                        serde_json::from_value(serde_json::Value::Object(json))
+                            .map_err(|e| format!("vmess 配置格式错误: {}", e))?
+                    } else {
+                        vmess_from_legacy_json(&json)
+                    }
+                };
+                if config.uuid.is_empty() {
+                    config.uuid = username.to_string();
+                }
+                config.normalize();
+                Ok(NodeConfig::Vmess(config))
+            }
+            Protocol::Vless => {
+                let mut config = if extra.is_empty() {
+                    VlessConfig::default()
+                } else if extra.starts_with("vless://") {
+                    let url = Url::parse(extra).map_err(|e| format!("vless 链接格式错误: {}", e))?;
+                    vless_from_query(&url)
+                } else {
+                    serde_json::from_str(extra).map_err(|e| format!("vless 配置格式错误: {}", e))?
+                };
+                if config.uuid.is_empty() {
+                    config.uuid = username.to_string();
+                }
+                Ok(NodeConfig::Vless(config))
+            }
+            Protocol::Trojan => {
+                let mut config = if extra.is_empty() {
+                    TrojanConfig::default()
+                } else if extra.starts_with("trojan://") {
+                    let url = Url::parse(extra).map_err(|e| format!("trojan 链接格式错误: {}", e))?;
+                    trojan_from_query(&url)
+                } else {
+                    serde_json::from_str(extra).map_err(|e| format!("trojan 配置格式错误: {}", e))?
+                };
+                config.normalize();
+                Ok(NodeConfig::Trojan(config))
+            }
+            Protocol::Hysteria2 => {
+                if extra.is_empty() {
+                    Ok(NodeConfig::Hysteria2(Hysteria2Config::default()))
+                } else if extra.starts_with("hysteria2://") || extra.starts_with("hy2://") {
+                    let url = Url::parse(extra).map_err(|e| format!("hysteria2 链接格式错误: {}", e))?;
+                    Ok(NodeConfig::Hysteria2(hysteria2_from_query(&url)))
+                } else {
+                    serde_json::from_str(extra)
+                        .map(NodeConfig::Hysteria2)
+                        .map_err(|e| format!("hysteria2 配置格式错误: {}", e))
+                }
+            }
+            Protocol::Tuic => {
+                let mut config = if extra.is_empty() {
+                    TuicConfig::default()
+                } else if extra.starts_with("tuic://") {
+                    let url = Url::parse(extra).map_err(|e| format!("tuic 链接格式错误: {}", e))?;
+                    tuic_from_query(&url)
+                } else {
+                    serde_json::from_str(extra).map_err(|e| format!("tuic 配置格式错误: {}", e))?
+                };
+                if config.uuid.is_empty() {
+                    config.uuid = username.to_string();
+                }
+                Ok(NodeConfig::Tuic(config))
+            }
+            Protocol::Ssr => {
+                let mut config = if extra.is_empty() {
+                    SsrConfig::default()
+                } else {
+                    serde_json::from_str(extra).map_err(|e| format!("ssr 配置格式错误: {}", e))?
+                };
+                if config.cipher.is_empty() {
+                    config.cipher = username.to_string();
+                }
+                Ok(NodeConfig::Ssr(config))
+            }
+            Protocol::Ss => {
+                let mut config = if extra.is_empty() {
+                    // 旧数据中加密方式保存在 username 列
+                    SsConfig::default()
+                } else if extra.starts_with("ss://") {
+                    // 旧数据保存的分享链接，按分享链接解析以保留加密方式与插件
+                    match crate::share_link::parse_single_link(extra).map(|node| node.config) {
+                        Some(NodeConfig::Ss(config)) => config,
+                        _ => return Err("ss 链接格式错误".to_string()),
+                    }
+                } else {
+                    serde_json::from_str(extra).map_err(|e| format!("ss 配置格式错误: {}", e))?
+                };
+                if config.cipher.is_empty() {
+                    config.cipher = username.to_string();
+                }
+                Ok(NodeConfig::Ss(config))
+            }
+        }
+    }
+
+    // 序列化为 extra_config 存储格式，无额外配置时为 None
+    pub fn to_extra_config(&self) -> Option<String> {
+        match self {
+            NodeConfig::Plain => None,
+            NodeConfig::Ss(c) => serde_json::to_string(c).ok(),
+            NodeConfig::Ssr(c) => serde_json::to_string(c).ok(),
+            NodeConfig::Vmess(c) => serde_json::to_string(c).ok(),
+            NodeConfig::Vless(c) => serde_json::to_string(c).ok(),
+            NodeConfig::Trojan(c) => serde_json::to_string(c).ok(),
+            NodeConfig::Hysteria2(c) => serde_json::to_string(c).ok(),
+            NodeConfig::Tuic(c) => serde_json::to_string(c).ok(),
+        }
+    }
+
+    // 与配置同步的 username 列：vmess / vless / tuic 为 uuid，ss / ssr 为加密方式
+    pub fn username(&self) -> Option<&str> {
+        match self {
+            NodeConfig::Vmess(c) => Some(&c.uuid),
+            NodeConfig::Vless(c) => Some(&c.uuid),
+            NodeConfig::Tuic(c) => Some(&c.uuid),
+            NodeConfig::Ss(c) => Some(&c.cipher),
+            NodeConfig::Ssr(c) => Some(&c.cipher),
+            _ => None,
+        }
+    }
+
+    // 结构化的传输参数，没有传输层配置的协议返回 None
+    pub fn transport(&self) -> Option<Transport> {
+        match self {
+            NodeConfig::Vmess(c) => Some(c.transport()),
+            NodeConfig::Vless(c) => Some(c.transport()),
+            NodeConfig::Trojan(c) => Some(c.transport()),
+            _ => None,
+        }
+    }
+
+    // 结构化的 TLS 参数，没有 TLS 配置的协议返回 None
+    pub fn tls_options(&self) -> Option<TlsOptions> {
+        match self {
+            NodeConfig::Vmess(c) => Some(c.tls_options()),
+            NodeConfig::Vless(c) => Some(c.tls_options()),
+            NodeConfig::Trojan(c) => Some(c.tls_options()),
+            NodeConfig::Hysteria2(c) => Some(c.tls_options()),
+            NodeConfig::Tuic(c) => Some(c.tls_options()),
+            _ => None,
+        }
+    }
+
+    pub fn validate(&self, password: Option<&str>) -> Result<(), String> {
+        let has_password = password.map(|p| !p.is_empty()).unwrap_or(false);
+
+        match self {
+            NodeConfig::Plain => Ok(()),
+            NodeConfig::Vmess(c) => {
+                if !is_valid_vmess_id(&c.uuid) {
+                    return Err("vmess uuid 无效".to_string());
+                }
+                if c.alter_id < 0 || c.alter_id > 65535 {
+                    return Err(format!("vmess alter_id 超出范围: {}", c.alter_id));
+                }
+                if !VMESS_SECURITIES.contains(&c.security.as_str()) {
+                    return Err(format!("不支持的 vmess 加密方式: {}", c.security));
+                }
+                if !VMESS_NETWORKS.contains(&c.network.as_str()) {
+                    return Err(format!("不支持的 vmess 传输方式: {}", c.network));
+                }
+                Ok(())
+            }
+            NodeConfig::Vless(c) => {
+                if !is_valid_vmess_id(&c.uuid) {
+                    return Err("vless uuid 无效".to_string());
+                }
+                if !VLESS_SECURITIES.contains(&c.security.as_str()) {
+                    return Err(format!("不支持的 vless 安全类型: {}", c.security));
+                }
+                if c.security == "reality" && c.public_key.as_deref().is_none_or(str::is_empty) {
+                    return Err("vless reality 节点需要公钥".to_string());
+                }
+                if !VLESS_NETWORKS.contains(&c.network.as_str()) {
+                    return Err(format!("不支持的 vless 传输方式: {}", c.network));
+                }
+                if let Some(flow) = c.flow.as_deref().filter(|f| !f.is_empty()) {
+                    if !flow.starts_with("xtls-rprx-") {
+                        return Err(format!("不支持的 vless 流控: {}", flow));
+                    }
+                }
+                Ok(())
+            }
+            NodeConfig::Trojan(_) => {
+                if !has_password {
+                    return Err("trojan 节点需要密码".to_string());
+                }
+                Ok(())
+            }
+            NodeConfig::Hysteria2(c) => {
+                if !has_password {
+                    return Err("hysteria2 节点需要认证密码".to_string());
+                }
+                if let Some(obfs) = c.obfs.as_deref().filter(|o| !o.is_empty()) {
+                    if obfs != "salamander" {
+                        return Err(format!("不支持的 hysteria2 混淆类型: {}", obfs));
+                    }
+                    if c.obfs_password.as_deref().is_none_or(str::is_empty) {
+                        return Err("hysteria2 混淆需要混淆密码".to_string());
+                    }
+                }
+                Ok(())
+            }
+            NodeConfig::Tuic(c) => {
+                if !is_uuid(&c.uuid) {
+                    return Err("tuic uuid 无效".to_string());
+                }
+                if !has_password {
+                    return Err("tuic 节点需要密码".to_string());
+                }
+                if let Some(cc) = c.congestion_control.as_deref().filter(|v| !v.is_empty()) {
+                    if !TUIC_CONGESTION_CONTROLS.contains(&cc) {
+                        return Err(format!("不支持的 tuic 拥塞控制算法: {}", cc));
+                    }
+                }
+                if let Some(mode) = c.udp_relay_mode.as_deref().filter(|v| !v.is_empty()) {
+                    if !TUIC_UDP_RELAY_MODES.contains(&mode) {
+                        return Err(format!("不支持的 tuic UDP 转发模式: {}", mode));
+                    }
+                }
+                Ok(())
+            }
+            NodeConfig::Ss(c) => {
+                if !SS_CIPHERS.contains(&c.cipher.as_str()) {
+                    return Err(format!("不支持的 ss 加密方式: {}", c.cipher));
+                }
+                if !has_password && c.cipher != "none" && c.cipher != "plain" {
+                    return Err("ss 节点需要密码".to_string());
+                }
+                Ok(())
+            }
+            NodeConfig::Ssr(c) => {
+                if !SSR_CIPHERS.contains(&c.cipher.as_str()) {
+                    return Err(format!("不支持的 ssr 加密方式: {}", c.cipher));
+                }
+                if !SSR_PROTOCOLS.contains(&c.protocol.as_str()) {
+                    return Err(format!("不支持的 ssr 协议: {}", c.protocol));
+                }
+                if !SSR_OBFS.contains(&c.obfs.as_str()) {
+                    return Err(format!("不支持的 ssr 混淆方式: {}", c.obfs));
+                }
+                if !has_password {
+                    return Err("ssr 节点需要密码".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// 传输层参数，导出到各客户端配置与节点详情中使用
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Transport {
+    // tcp / ws / grpc / h2 / http / httpupgrade / kcp / quic
+    pub network: String,
+    pub host: Option<String>,
+    pub path: Option<String>,
+    // grpc 服务名
+    pub service_name: Option<String>,
+    // tcp 伪装类型，如 http
+    pub header_type: Option<String>,
+}
+
+// TLS 参数
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TlsOptions {
+    pub enabled: bool,
+    pub sni: Option<String>,
+    pub alpn: Vec<String>,
+    pub allow_insecure: bool,
+    // uTLS 指纹，如 chrome
+    pub fingerprint: Option<String>,
+}
+
+impl VmessConfig {
+    fn extra_str(&self, key: &str) -> Option<String> {
+        extra_str(&self.extra, key)
+    }
+
+    // 伪装类型只对 tcp 生效，kcp / quic 的 type 不导出到其他客户端
+    pub fn transport(&self) -> Transport {
+        let is_grpc = self.network == "grpc";
+        Transport {
+            network: self.network.clone(),
+            host: self.host.clone(),
+            path: if is_grpc { None } else { self.path.clone() },
+            service_name: if is_grpc { self.service_name.clone() } else { None },
+            header_type: self.header_type.clone().filter(|_| self.network == "tcp"),
+        }
+    }
+
+    // v2rayN 格式中写在 path 里的值：grpc 为服务名，其余为路径
+    pub fn share_path(&self) -> Option<&str> {
+        if self.network == "grpc" {
+            self.service_name.as_deref()
+        } else {
+            self.path.as_deref()
+        }
+    }
+
+    // 空值与 none 视为未设置；旧数据中 grpc 的服务名保存在 path 中，移到 service_name，
+    // 其余传输方式没有服务名
+    fn normalize(&mut self) {
+        normalize_transport(&mut self.host, &mut self.path, &mut self.service_name, &mut self.header_type);
+        if self.network == "grpc" {
+            let path = self.path.take();
+            self.service_name = self.service_name.take().or(path);
+        } else {
+            self.service_name = None;
+        }
+    }
+
+    // transport() / tls_options() 的逆操作，导入其他客户端的配置时使用
+    pub fn from_parts(uuid: String, alter_id: i64, security: String, transport: Transport, tls: TlsOptions) -> Self {
+        let mut extra = BTreeMap::new();
+        if tls.enabled {
+            put_extra(&mut extra, "sni", tls.sni);
+            put_extra(&mut extra, "alpn", Some(tls.alpn.join(",")));
+            if tls.allow_insecure {
+                put_extra(&mut extra, "allowInsecure", Some("1".to_string()));
+            }
+            put_extra(&mut extra, "fp", tls.fingerprint);
+        }
+
+        VmessConfig {
+            uuid,
+            alter_id,
+            security: if security.is_empty() { "auto".to_string() } else { security },
+            network: if transport.network.is_empty() { "tcp".to_string() } else { transport.network },
+            tls: tls.enabled,
+            host: transport.host,
+            path: transport.path,
+            service_name: transport.service_name,
+            header_type: transport.header_type,
+            extra,
+        }
+    }
+
+    pub fn tls_options(&self) -> TlsOptions {
+        TlsOptions {
+            enabled: self.tls,
+            sni: self.extra_str("sni"),
+            alpn: self.extra_str("alpn").map(|a| split_alpn(&a)).unwrap_or_default(),
+            allow_insecure: self.extra_str("allowInsecure").map(|v| is_truthy(&v)).unwrap_or(false),
+            fingerprint: self.extra_str("fp"),
+        }
+    }
+}
+
+impl VlessConfig {
+    pub fn transport(&self) -> Transport {
+        Transport {
+            network: self.network.clone(),
+            host: self.host.clone().filter(|h| !h.is_empty()),
+            path: self.path.clone().filter(|p| !p.is_empty()),
+            service_name: self.service_name.clone().filter(|s| !s.is_empty()),
+            header_type: self.header_type.clone().filter(|t| !t.is_empty() && t != "none"),
+        }
+    }
+
+    // REALITY 同样使用 sni 与指纹，公钥等参数直接读取配置
+    pub fn tls_options(&self) -> TlsOptions {
+        TlsOptions {
+            enabled: self.security != "none",
+            sni: self.sni.clone().filter(|s| !s.is_empty()),
+            alpn: self.alpn.clone(),
+            allow_insecure: self.allow_insecure,
+            fingerprint: self.fingerprint.clone().filter(|f| !f.is_empty()),
+        }
+    }
+
+    pub fn is_reality(&self) -> bool {
+        self.security == "reality"
+    }
+
+    // 应用 transport() / tls_options() 的结果，导入其他客户端的配置时使用
+    pub fn set_parts(&mut self, transport: Transport, tls: TlsOptions) {
+        self.network = if transport.network.is_empty() { "tcp".to_string() } else { transport.network };
+        self.host = transport.host;
+        self.path = transport.path;
+        self.service_name = transport.service_name;
+        self.header_type = transport.header_type;
+        if tls.enabled && self.security == "none" {
+            self.security = "tls".to_string();
+        }
+        self.sni = tls.sni;
+        self.alpn = tls.alpn;
+        self.allow_insecure = tls.allow_insecure;
+        self.fingerprint = tls.fingerprint;
+    }
+}
+
+impl TrojanConfig {
+    fn extra_str(&self, key: &str) -> Option<String> {
+        extra_str(&self.extra, key)
+    }
+
+    pub fn transport(&self) -> Transport {
+        Transport {
+            network: self.network.clone(),
+            host: self.host.clone(),
+            path: self.path.clone(),
+            service_name: self.service_name.clone(),
+            header_type: self.header_type.clone(),
+        }
+    }
+
+    // 空值与 none 视为未设置
+    fn normalize(&mut self) {
+        if self.network.trim().is_empty() {
+            self.network = "tcp".to_string();
+        }
+        normalize_transport(&mut self.host, &mut self.path, &mut self.service_name, &mut self.header_type);
+    }
+
+    // transport() / tls_options() 的逆操作，导入其他客户端的配置时使用
+    pub fn from_parts(transport: Transport, tls: TlsOptions) -> Self {
+        let mut extra = BTreeMap::new();
+        put_extra(&mut extra, "fp", tls.fingerprint);
+
+        TrojanConfig {
+            sni: tls.sni,
+            alpn: tls.alpn,
+            allow_insecure: tls.allow_insecure,
+            network: if transport.network.is_empty() { "tcp".to_string() } else { transport.network },
+            host: transport.host,
+            path: transport.path,
+            service_name: transport.service_name,
+            header_type: transport.header_type,
+            extra,
+        }
+    }
+
+    pub fn tls_options(&self) -> TlsOptions {
+        TlsOptions {
+            enabled: true,
+            sni: self.sni.clone().filter(|s| !s.is_empty()),
+            alpn: self.alpn.clone(),
+            allow_insecure: self.allow_insecure,
+            fingerprint: self.extra_str("fp"),
+        }
+    }
+}
+
+impl Hysteria2Config {
+    // Hysteria2 基于 QUIC，TLS 始终启用
+    pub fn tls_options(&self) -> TlsOptions {
+        TlsOptions {
+            enabled: true,
+            sni: self.sni.clone().filter(|s| !s.is_empty()),
+            alpn: self.alpn.clone(),
+            allow_insecure: self.allow_insecure,
+            fingerprint: None,
+        }
+    }
+
+    // 带宽提示换算为 Mbps，客户端配置大多只接受整数 Mbps
+    pub fn up_mbps(&self) -> Option<u64> {
+        self.up.as_deref().and_then(bandwidth_mbps)
+    }
+
+    pub fn down_mbps(&self) -> Option<u64> {
+        self.down.as_deref().and_then(bandwidth_mbps)
+    }
+}
+
+impl TuicConfig {
+    pub fn tls_options(&self) -> TlsOptions {
+        TlsOptions {
+            enabled: true,
+            sni: self.sni.clone().filter(|s| !s.is_empty()),
+            alpn: self.alpn.clone(),
+            allow_insecure: self.allow_insecure,
+            fingerprint: None,
+        }
+    }
+}
+
+// "100"、"100 Mbps"、"1 Gbps"、"500kbps" 等带宽写法，不带单位时按 Mbps 处理
+fn bandwidth_mbps(value: &str) -> Option<u64> {
+    let value = value.trim().to_lowercase();
+    let split = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let number: f64 = value[..split].parse().ok()?;
+    let factor = match value[split..].trim() {
+        "" | "m" | "mbps" => 1.0,
+        "g" | "gbps" => 1000.0,
+        "k" | "kbps" => 0.001,
+        _ => return None,
+    };
+    let mbps = (number * factor).round();
+    (mbps >= 1.0).then_some(mbps as u64)
+}
+
+impl SsConfig {
+    // SIP003 插件参数，如 "obfs-local;obfs=http;obfs-host=example.com"，
+    // 返回插件名与参数，没有值的参数（如 tls）记为 "true"
+    pub fn plugin_options(&self) -> Option<(String, BTreeMap<String, String>)> {
+        let plugin = self.plugin.as_deref().map(str::trim).filter(|p| !p.is_empty())?;
+        let mut parts = plugin.split(';');
+        let name = parts.next()?.trim().to_string();
+        let options = parts
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(|part| match part.split_once('=') {
+                Some((key, value)) => (key.trim().to_string(), value.trim().to_string()),
+                None => (part.to_string(), "true".to_string()),
+            })
+            .collect();
+        Some((name, options))
+    }
+}
+
+fn extra_str(extra: &BTreeMap<String, serde_json::Value>, key: &str) -> Option<String> {
+    match extra.get(key)? {
+        serde_json::Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn normalize_transport(
+    host: &mut Option<String>,
+    path: &mut Option<String>,
+    service_name: &mut Option<String>,
+    header_type: &mut Option<String>,
+) {
+    for value in [host, path, service_name] {
+        *value = value.take().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    }
+    *header_type = header_type.take().map(|v| v.trim().to_string()).filter(|v| !v.is_empty() && v != "none");
+}
+
+fn put_extra(extra: &mut BTreeMap<String, serde_json::Value>, key: &str, value: Option<String>) {
+    if let Some(value) = value.filter(|v| !v.is_empty()) {
+        extra.insert(key.to_string(), serde_json::Value::String(value));
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    value == "1" || value.eq_ignore_ascii_case("true")
+}
+
+// 读取已存储节点的协议与配置
+pub fn node_config(node: &ProxyNode) -> Result<(Protocol, NodeConfig), String> {
+    let protocol = Protocol::parse(&node.protocol)
+        .ok_or_else(|| format!("不支持的协议: {}", node.protocol))?;
+    let config = NodeConfig::from_stored(protocol, node.extra_config.as_deref(), node.username.as_deref())?;
+    Ok((protocol, config))
+}
+
+// 已存储节点的指纹，旧数据没有保存指纹时现场计算
+pub fn node_fingerprint(node: &ProxyNode) -> Option<String> {
+    if let Some(fp) = node.fingerprint.as_deref().filter(|f| !f.is_empty()) {
+        return Some(fp.to_string());
+    }
+    let (protocol, config) = node_config(node).ok()?;
+    Some(fingerprint(protocol, &node.address, node.port, node.username.as_deref(), node.password.as_deref(), &config))
+}
+
+// 校验后的节点字段
+pub struct ValidatedNode {
+    pub protocol: Protocol,
+    pub username: Option<String>,
+    pub extra_config: Option<String>,
+    pub fingerprint: String,
+}
+
+// 校验节点的协议、地址与协议配置，返回规范化后的 username / extra_config
+pub fn validate_node(
+    protocol: &str,
+    address: &str,
+    port: i64,
+    username: Option<&str>,
+    password: Option<&str>,
+    extra_config: Option<&str>,
+) -> Result<ValidatedNode, String> {
+    let protocol = Protocol::parse(protocol).ok_or_else(|| format!("不支持的协议: {}", protocol))?;
+
+    if address.trim().is_empty() {
+        return Err("节点地址不能为空".to_string());
+    }
+    if !(1..=65535).contains(&port) {
+        return Err(format!("端口超出范围: {}", port));
+    }
+
+    let config = NodeConfig::from_stored(protocol, extra_config, username)?;
+    config.validate(password)?;
+
+    let username = config.username()
+        .map(|u| u.to_string())
+        .or_else(|| username.map(|u| u.to_string()));
+
+    Ok(ValidatedNode {
+        protocol,
+        fingerprint: fingerprint(protocol, address, port, username.as_deref(), password, &config),
+        username,
+        extra_config: config.to_extra_config(),
+    })
+}
+
+// 节点指纹：协议、地址、端口、凭据与传输参数的 SHA-256，
+// 不包含名称等展示字段，用于导入时识别同一个节点
+pub fn fingerprint(
+    protocol: Protocol,
+    address: &str,
+    port: i64,
+    username: Option<&str>,
+    password: Option<&str>,
+    config: &NodeConfig,
+) -> String {
+    let transport = match config {
+        NodeConfig::Plain => String::new(),
+        NodeConfig::Ss(c) => c.plugin.clone().unwrap_or_default(),
+        NodeConfig::Ssr(c) => [
+            c.protocol.as_str(),
+            c.protocol_param.as_deref().unwrap_or(""),
+            c.obfs.as_str(),
+            c.obfs_param.as_deref().unwrap_or(""),
+        ].join("|"),
+        NodeConfig::Vmess(c) => format!(
+            "{}|{}|{}|{}|{}|{}",
+            c.network,
+            c.tls,
+            c.header_type.as_deref().unwrap_or(""),
+            c.host.as_deref().unwrap_or(""),
+            c.share_path().unwrap_or(""),
+            transport_extra(&c.extra, &["sni"])
+        ),
+        NodeConfig::Vless(c) => [
+            c.network.as_str(),
+            c.security.as_str(),
+            c.flow.as_deref().unwrap_or(""),
+            c.sni.as_deref().unwrap_or(""),
+            c.host.as_deref().unwrap_or(""),
+            c.path.as_deref().unwrap_or(""),
+            c.service_name.as_deref().unwrap_or(""),
+            c.public_key.as_deref().unwrap_or(""),
+        ].join("|"),
+        NodeConfig::Trojan(c) => [
+            c.sni.as_deref().unwrap_or(""),
+            c.network.as_str(),
+            c.host.as_deref().unwrap_or(""),
+            c.path.as_deref().unwrap_or(""),
+            c.service_name.as_deref().unwrap_or(""),
+        ].join("|"),
+        NodeConfig::Hysteria2(c) => [
+            c.sni.as_deref().unwrap_or(""),
+            c.obfs.as_deref().unwrap_or(""),
+            c.obfs_password.as_deref().unwrap_or(""),
+        ].join("|"),
+        NodeConfig::Tuic(c) => c.sni.clone().unwrap_or_default(),
+    };
+
+    let source = format!(
+        "{}|{}|{}|{}|{}|{}",
+        protocol.as_str(),
+        address.trim().to_lowercase(),
+        port,
+        username.unwrap_or(""),
+        password.unwrap_or(""),
+        transport
+    );
+    let mut hasher = Sha256::new();
+    hasher.update(source.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+// 读取指纹中使用的传输参数
+fn transport_extra(extra: &BTreeMap<String, serde_json::Value>, keys: &[&str]) -> String {
+    keys.iter()
+        .map(|key| match extra.get(*key) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn parse_json_object(text: &str) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    match serde_json::from_str(text) {
+        Ok(serde_json::Value::Object(map)) => Ok(map),
+        Ok(_) => Err("配置必须是 JSON 对象".to_string()),
+        Err(e) => Err(format!("配置不是有效的 JSON: {}", e)),
+    }
+}
+
+// vmess 分享链接中的 JSON（v2rayN 格式），数值字段可能是字符串
+fn vmess_from_legacy_json(json: &serde_json::Map<String, serde_json::Value>) -> VmessConfig {
+    let text = |key: &str| -> Option<String> {
+        match json.get(key)? {
+            serde_json::Value::String(s) => Some(s.trim().to_string()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    };
+
+    let mut config = VmessConfig {
+        uuid: text("id").unwrap_or_default(),
+        alter_id: text("aid").and_then(|v| v.parse().ok()).unwrap_or(0),
+        security: text("scy").filter(|v| !v.is_empty()).unwrap_or_else(|| "auto".to_string()),
+        network: text("net").filter(|v| !v.is_empty()).unwrap_or_else(|| "tcp".to_string()),
+        tls: text("tls").map(|v| v == "tls").unwrap_or(false),
+        host: text("host"),
+        path: text("path"),
+        service_name: text("serviceName"),
+        header_type: text("type"),
+        extra: BTreeMap::new(),
+    };
+    config.normalize();
+
+    // 节点名、地址、端口已存放在独立的列中
+    const KNOWN_KEYS: &[&str] = &["v", "ps", "add", "port", "id", "aid", "scy", "net", "tls", "host", "path", "serviceName", "type"];
+    for (key, value) in json {
+        let is_empty = value.as_str().map(|s| s.is_empty()).unwrap_or(false);
+        if !KNOWN_KEYS.contains(&key.as_str()) && !is_empty {
+            config.extra.insert(key.clone(), value.clone());
+        }
+    }
+
+    config
+}
+
+// trojan 分享链接的查询参数
+fn trojan_from_query(url: &Url) -> TrojanConfig {
+    let mut config = TrojanConfig::default();
+    let text = |value: &str| Some(value.to_string()).filter(|v| !v.is_empty());
+
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "sni" | "peer" if !value.is_empty() => config.sni = Some(value.to_string()),
+            "alpn" => config.alpn = split_alpn(&value),
+            "allowInsecure" | "allow_insecure" | "insecure" => config.allow_insecure = is_truthy(&value),
+            "type" if !value.is_empty() => config.network = value.to_lowercase(),
+            "host" => config.host = text(&value),
+            "path" => config.path = text(&value),
+            "serviceName" => config.service_name = text(&value),
+            "headerType" => config.header_type = text(&value).filter(|t| t != "none"),
+            _ if !value.is_empty() => {
+                config.extra.insert(key.to_string(), serde_json::Value::String(value.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    config
+}
+
+// vless 分享链接的查询参数
+fn vless_from_query(url: &Url) -> VlessConfig {
+    let mut config = VlessConfig::default();
+    let text = |value: &str| Some(value.to_string()).filter(|v| !v.is_empty());
+
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "flow" => config.flow = text(&value),
+            "security" if !value.is_empty() => config.security = value.to_lowercase(),
+            "sni" | "peer" => config.sni = text(&value),
+            "alpn" => config.alpn = split_alpn(&value),
+            "fp" => config.fingerprint = text(&value),
+            "allowInsecure" | "allow_insecure" | "insecure" => config.allow_insecure = is_truthy(&value),
+            "pbk" => config.public_key = text(&value),
+            "sid" => config.short_id = text(&value),
+            "spx" => config.spider_x = text(&value),
+            "type" if !value.is_empty() => config.network = value.to_lowercase(),
+            "host" => config.host = text(&value),
+            "path" => config.path = text(&value),
+            "serviceName" => config.service_name = text(&value),
+            "headerType" => config.header_type = text(&value).filter(|t| t != "none"),
+            // 标准 VLESS 不加密，encryption=none 无需保存
+            "encryption" if value == "none" || value.is_empty() => {}
+            _ if !value.is_empty() => {
+                config.extra.insert(key.to_string(), serde_json::Value::String(value.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    config
+}
+
+// hysteria2 分享链接的查询参数
+fn hysteria2_from_query(url: &Url) -> Hysteria2Config {
+    let mut config = Hysteria2Config::default();
+    let text = |value: &str| Some(value.to_string()).filter(|v| !v.is_empty());
+
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "sni" | "peer" => config.sni = text(&value),
+            "alpn" => config.alpn = split_alpn(&value),
+            "insecure" | "allowInsecure" | "allow_insecure" => config.allow_insecure = is_truthy(&value),
+            "obfs" => config.obfs = text(&value).filter(|o| o != "none"),
+            "obfs-password" | "obfs_password" => config.obfs_password = text(&value),
+            "up" | "upmbps" => config.up = text(&value),
+            "down" | "downmbps" => config.down = text(&value),
+            _ if !value.is_empty() => {
+                config.extra.insert(key.to_string(), serde_json::Value::String(value.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    config
+}
+
+// tuic 分享链接的查询参数，uuid 与密码在链接的用户信息部分
+fn tuic_from_query(url: &Url) -> TuicConfig {
+    let mut config = TuicConfig {
+        uuid: crate::utils::percent_decode(url.username()),
+        ..Default::default()
+    };
+    let text = |value: &str| Some(value.to_string()).filter(|v| !v.is_empty());
+
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "sni" | "peer" => config.sni = text(&value),
+            "alpn" => config.alpn = split_alpn(&value),
+            "allow_insecure" | "allowInsecure" | "insecure" => config.allow_insecure = is_truthy(&value),
+            "congestion_control" | "congestion_controller" => config.congestion_control = text(&value),
+            "udp_relay_mode" => config.udp_relay_mode = text(&value),
+            _ if !value.is_empty() => {
+                config.extra.insert(key.to_string(), serde_json::Value::String(value.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    config
+}
+
+// 逗号分隔的 ALPN 列表
+pub fn split_alpn(value: &str) -> Vec<String> {
+    value.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+// vmess id：标准 UUID，或 1-30 字节的字符串（Xray 会映射为 UUID）
+fn is_valid_vmess_id(id: &str) -> bool {
+    if id.len() == 36 {
+        is_uuid(id)
+    } else {
+        !id.is_empty() && id.len() <= 30
+    }
+}
+
+// 标准 UUID 格式 xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx
+fn is_uuid(id: &str) -> bool {
+    id.len() == 36 && id.chars().enumerate().all(|(i, c)| match i {
+        8 | 13 | 18 | 23 => c == '-',
+        _ => c.is_ascii_hexdigit(),
+    })
+}
@@ -0,0 +1,169 @@
+// 从节点名称中识别地区：依次尝试国旗 emoji、中英文地名、ISO 代码，
+// 返回 ISO 3166-1 alpha-2 地区代码
+
+// 地区代码及其中英文名称、城市名，顺序即匹配优先级（较长、较具体的名称在前）
+const REGION_KEYWORDS: &[(&str, &[&str])] = &[
+    ("HK", &["香港", "Hong Kong", "HongKong"]),
+    ("MO", &["澳门", "澳門", "Macau", "Macao"]),
+    ("TW", &["台湾", "臺灣", "台灣", "台北", "Taiwan", "Taipei"]),
+    ("JP", &["日本", "东京", "東京", "大阪", "Japan", "Tokyo", "Osaka"]),
+    ("KR", &["韩国", "韓國", "首尔", "Korea", "Seoul"]),
+    ("SG", &["新加坡", "狮城", "Singapore"]),
+    ("US", &["美国", "美國", "洛杉矶", "圣何塞", "硅谷", "西雅图", "纽约", "芝加哥", "达拉斯",
+             "United States", "America", "Los Angeles", "San Jose", "Silicon Valley", "Seattle",
+             "New York", "Chicago", "Dallas"]),
+    ("CA", &["加拿大", "多伦多", "温哥华", "Canada", "Toronto", "Vancouver"]),
+    ("GB", &["英国", "英國", "伦敦", "United Kingdom", "Britain", "London"]),
+    ("DE", &["德国", "德國", "法兰克福", "Germany", "Frankfurt"]),
+    ("FR", &["法国", "法國", "巴黎", "France", "Paris"]),
+    ("NL", &["荷兰", "荷蘭", "阿姆斯特丹", "Netherlands", "Amsterdam"]),
+    ("RU", &["俄罗斯", "俄羅斯", "莫斯科", "Russia", "Moscow"]),
+    ("TR", &["土耳其", "伊斯坦布尔", "Turkey", "Türkiye", "Istanbul"]),
+    ("ID", &["印度尼西亚", "印尼", "雅加达", "Indonesia", "Jakarta"]),
+    ("IN", &["印度", "孟买", "India", "Mumbai"]),
+    ("MY", &["马来西亚", "馬來西亞", "吉隆坡", "Malaysia", "Kuala Lumpur"]),
+    ("TH", &["泰国", "泰國", "曼谷", "Thailand", "Bangkok"]),
+    ("VN", &["越南", "Vietnam"]),
+    ("PH", &["菲律宾", "Philippines", "Manila"]),
+    ("AU", &["澳大利亚", "澳洲", "悉尼", "Australia", "Sydney"]),
+    ("BR", &["巴西", "Brazil"]),
+    ("AR", &["阿根廷", "Argentina"]),
+    ("AE", &["阿联酋", "迪拜", "Dubai", "United Arab Emirates"]),
+    ("UA", &["乌克兰", "Ukraine"]),
+    ("CN", &["中国", "回国", "China"]),
+];
+
+// 常见的三位代码与非标准写法
+const CODE_ALIASES: &[(&str, &str)] = &[
+    ("UK", "GB"),
+    ("USA", "US"),
+    ("HKG", "HK"),
+    ("TWN", "TW"),
+    ("JPN", "JP"),
+    ("KOR", "KR"),
+    ("SGP", "SG"),
+    ("GBR", "GB"),
+    ("DEU", "DE"),
+];
+
+// 线路类型等常见缩写与单词，即使与地区代码相同也不作为地区代码
+const NON_REGION_TOKENS: &[&str] = &[
+    "CN2", "GIA", "IPLC", "IEPL", "BGP", "CMI", "CU", "CT", "CM",
+    // 印度、印度尼西亚只按地名与国旗识别，避免 "ID"、"IN" 单词误判
+    "ID", "IN",
+];
+
+pub fn detect_region(name: &str) -> Option<String> {
+    detect_flag(name)
+        .or_else(|| detect_place_name(name))
+        .or_else(|| detect_code(name))
+}
+
+// 国旗 emoji 由两个区域指示符号组成，对应两位地区代码
+fn detect_flag(name: &str) -> Option<String> {
+    let indicator = |c: char| -> Option<char> {
+        let offset = (c as u32).checked_sub(0x1F1E6)?;
+        if offset < 26 {
+            char::from_u32('A' as u32 + offset)
+        } else {
+            None
+        }
+    };
+
+    let chars: Vec<char> = name.chars().collect();
+    chars.windows(2).find_map(|pair| {
+        Some([indicator(pair[0])?, indicator(pair[1])?].iter().collect())
+    })
+}
+
+// 按名称中最先出现的地名确定地区
+fn detect_place_name(name: &str) -> Option<String> {
+    let lower = name.to_lowercase();
+    let mut best: Option<(usize, &str)> = None;
+
+    for (code, keywords) in REGION_KEYWORDS {
+        for keyword in *keywords {
+            let Some(pos) = find_keyword(&lower, &keyword.to_lowercase()) else { continue };
+            if best.map(|(best_pos, _)| pos < best_pos).unwrap_or(true) {
+                best = Some((pos, code));
+            }
+        }
+    }
+
+    best.map(|(_, code)| code.to_string())
+}
+
+// 英文地名需要完整单词匹配，避免 "India" 命中 "Indiana"
+fn find_keyword(haystack: &str, keyword: &str) -> Option<usize> {
+    if !keyword.is_ascii() {
+        return haystack.find(keyword);
+    }
+
+    let is_word_char = |c: Option<char>| c.map(|c| c.is_ascii_alphabetic()).unwrap_or(false);
+    let mut start = 0;
+    while let Some(offset) = haystack[start..].find(keyword) {
+        let pos = start + offset;
+        let end = pos + keyword.len();
+        if !is_word_char(haystack[..pos].chars().next_back()) && !is_word_char(haystack[end..].chars().next()) {
+            return Some(pos);
+        }
+        start = end;
+    }
+    None
+}
+
+// 名称中独立出现的大写地区代码，如 "JP-Tokyo-IPLC"、"香港 HK 01"；
+// 与数字或小写字母相连的（如 "CN2"、"HK01"、"10GB"、"Tokyo"）不是地区代码
+fn detect_code(name: &str) -> Option<String> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|token| token.len() >= 2 && token.chars().all(|c| c.is_ascii_uppercase()))
+        .filter(|token| !NON_REGION_TOKENS.contains(token))
+        .find_map(|token| {
+            if let Some((_, code)) = CODE_ALIASES.iter().find(|(alias, _)| alias == &token) {
+                return Some(code.to_string());
+            }
+            REGION_KEYWORDS.iter()
+                .find(|(code, _)| code == &token)
+                .map(|(code, _)| code.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag() {
+        assert_eq!(detect_region("🇯🇵 Node 01").as_deref(), Some("JP"));
+        // 国旗优先于名称中的其他地名
+        assert_eq!(detect_region("🇸🇬 香港中转").as_deref(), Some("SG"));
+    }
+
+    #[test]
+    fn place_name() {
+        assert_eq!(detect_region("香港 01").as_deref(), Some("HK"));
+        assert_eq!(detect_region("Los Angeles | IPLC").as_deref(), Some("US"));
+        assert_eq!(detect_region("日本-台湾 中转").as_deref(), Some("JP"));
+        assert_eq!(detect_region("Tokyo-Test").as_deref(), Some("JP"));
+        assert_eq!(detect_region("Indiana 01").as_deref(), None);
+    }
+
+    #[test]
+    fn code() {
+        assert_eq!(detect_region("JP-IPLC-01").as_deref(), Some("JP"));
+        assert_eq!(detect_region("[HK] 01").as_deref(), Some("HK"));
+        assert_eq!(detect_region("UK 02").as_deref(), Some("GB"));
+        assert_eq!(detect_region("节点_SGP").as_deref(), Some("SG"));
+    }
+
+    #[test]
+    fn code_false_positives() {
+        assert_eq!(detect_region("CN2 GIA 01").as_deref(), None);
+        assert_eq!(detect_region("HK01").as_deref(), None);
+        assert_eq!(detect_region("剩余流量 10GB").as_deref(), None);
+        assert_eq!(detect_region("IPLC 专线").as_deref(), None);
+        assert_eq!(detect_region("Node ID 3").as_deref(), None);
+        assert_eq!(detect_region("CHECK IN").as_deref(), None);
+        assert_eq!(detect_region("CN2 GIA | US").as_deref(), Some("US"));
+    }
+}
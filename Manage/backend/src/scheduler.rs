@@ -0,0 +1,101 @@
+use worker::*;
+use crate::checker;
+use crate::db;
+use crate::handlers::proxy;
+use crate::utils;
+
+// 定时任务使用的 KV 命名空间
+const KV_BINDING: &str = "SESSION_KV";
+// 保存定时检测进度（上次检测到的节点 id）
+const HEALTH_CHECK_CURSOR_KEY: &str = "health_check:cursor";
+
+// 分组未配置时的默认检测间隔（分钟）
+pub const DEFAULT_CHECK_INTERVAL_MINUTES: i64 = 30;
+// 单次触发最多检测的节点数
+const HEALTH_CHECK_BATCH_SIZE: usize = 25;
+// 单次触发的时间预算（毫秒），超出后保存进度留给下一次触发
+const HEALTH_CHECK_BUDGET_MS: f64 = 20_000.0;
+
+// 订阅定时刷新的最小间隔（分钟）
+pub const MIN_SUBSCRIPTION_REFRESH_MINUTES: i64 = 10;
+// 单次触发最多刷新的订阅数
+const SUBSCRIPTION_REFRESH_BATCH_SIZE: usize = 3;
+
+// 原始检测记录保留天数，之后按小时聚合
+pub const CHECK_HISTORY_RETENTION_DAYS: i64 = 7;
+// 小时聚合数据保留天数
+const CHECK_HOURLY_RETENTION_DAYS: i64 = 90;
+
+// 定时检测：从 KV 中的 cursor 继续，按批检测到期节点，
+// 一轮完整扫描可能跨越多次触发
+pub async fn run_health_checks(env: &Env) -> Result<()> {
+    let db = env.d1("DB")?;
+    let kv = env.kv(KV_BINDING)?;
+
+    let cursor: i64 = kv.get(HEALTH_CHECK_CURSOR_KEY).text().await?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    let nodes = db::list_due_proxy_nodes(&db, cursor, DEFAULT_CHECK_INTERVAL_MINUTES, HEALTH_CHECK_BATCH_SIZE).await?;
+
+    // cursor 之后没有到期节点，本轮扫描结束，下次从头开始
+    if nodes.is_empty() {
+        if cursor != 0 {
+            kv.put(HEALTH_CHECK_CURSOR_KEY, "0")?.execute().await?;
+        }
+        return Ok(());
+    }
+
+    let batch_max_id = nodes.iter().map(|n| n.id).max().unwrap_or(cursor);
+    let outcome = checker::check_proxy_nodes(nodes, HEALTH_CHECK_BUDGET_MS).await;
+    db::batch_update_proxy_node_status(&db, &outcome.results).await?;
+
+    // 有节点未完成时，cursor 停在最小的未完成节点之前，下次触发从那里继续
+    let last_id = match outcome.skipped.iter().min() {
+        Some(min_skipped) => min_skipped - 1,
+        None => batch_max_id,
+    };
+    let checked = outcome.results.len();
+
+    kv.put(HEALTH_CHECK_CURSOR_KEY, last_id.to_string())?.execute().await?;
+    console_log!("scheduled health check: {} nodes checked, cursor {} -> {}", checked, cursor, last_id);
+
+    Ok(())
+}
+
+// 检测历史降采样：超过保留期的原始记录聚合为小时数据
+pub async fn downsample_check_history(env: &Env) -> Result<()> {
+    let db = env.d1("DB")?;
+
+    // 对齐到整点，保证同一小时的记录一起聚合
+    let now = (js_sys::Date::now() / 1000.0) as i64;
+    let cutoff = (now - CHECK_HISTORY_RETENTION_DAYS * 86400) / 3600 * 3600;
+    let hourly_cutoff = (now - CHECK_HOURLY_RETENTION_DAYS * 86400) / 3600 * 3600;
+
+    db::downsample_proxy_node_checks(
+        &db,
+        &utils::format_sqlite_datetime(cutoff),
+        &utils::format_sqlite_datetime(hourly_cutoff),
+    ).await
+}
+
+// 定时刷新到期的订阅，上游已不再提供的节点标记为失效，
+// 刷新结果记录在订阅信息中
+pub async fn refresh_due_subscriptions(env: &Env) -> Result<()> {
+    let db = env.d1("DB")?;
+
+    let due = db::list_due_subscriptions(&db, SUBSCRIPTION_REFRESH_BATCH_SIZE).await?;
+
+    for info in due {
+        let url = info.subscription_url.as_deref().unwrap_or("");
+        match proxy::refresh_subscription(&db, &info.group_name, url, false).await {
+            Ok(result) => console_log!(
+                "scheduled subscription refresh {}: {} added, {} updated, {} vanished",
+                info.group_name, result.added, result.updated, result.vanished
+            ),
+            Err(e) => console_error!("scheduled subscription refresh {} failed: {}", info.group_name, e),
+        }
+    }
+
+    Ok(())
+}
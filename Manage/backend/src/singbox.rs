@@ -0,0 +1,407 @@
+// sing-box 配置：按协议输出 outbounds 并为每个节点分组生成 selector，另附 urltest 自动选择；
+// 以及导入 sing-box 配置中的代理出站
+
+use serde_json::{json, Map, Value};
+use crate::export::{ProxyLayout, UniqueNames, AUTO_GROUP, SELECT_GROUP, URL_TEST_INTERVAL, URL_TEST_URL};
+use crate::models::{NodeGroup, ProxyNode};
+use crate::protocol::{self, Hysteria2Config, NodeConfig, Protocol, SsConfig, TlsOptions, Transport, TrojanConfig, TuicConfig, VlessConfig, VmessConfig};
+use crate::share_link::{ParsedNode, ParsedSubscription, UnsupportedEntry};
+use crate::utils::{json_bool, json_list, json_object, json_str};
+
+const DIRECT_TAG: &str = "direct";
+// 本地 mixed 入站（HTTP + SOCKS5）监听端口
+const MIXED_LISTEN_PORT: u16 = 2080;
+
+// 生成完整配置，返回 JSON 文本与无法表示而跳过的节点 id
+pub fn render(nodes: &[ProxyNode], groups: &[NodeGroup]) -> Result<(String, Vec<i64>), String> {
+    let mut names = UniqueNames::new(&[DIRECT_TAG, SELECT_GROUP, AUTO_GROUP]);
+    let mut proxies: Vec<Value> = Vec::new();
+    let mut skipped = Vec::new();
+    let mut layout = ProxyLayout::default();
+
+    for node in nodes {
+        let tag = names.claim(&node.name);
+        match outbound(node, &tag) {
+            Ok(outbound) => {
+                proxies.push(outbound);
+                layout.add(node.group_name.as_deref(), tag);
+            }
+            Err(_) => {
+                names.release(&tag);
+                skipped.push(node.id);
+            }
+        }
+    }
+    let layout = layout.finish(groups, &mut names);
+
+    let mut outbounds = vec![json!({
+        "type": "selector",
+        "tag": SELECT_GROUP,
+        "outbounds": layout.selector_members(DIRECT_TAG),
+    })];
+    if !layout.all.is_empty() {
+        outbounds.push(json!({
+            "type": "urltest",
+            "tag": AUTO_GROUP,
+            "outbounds": layout.all,
+            "url": URL_TEST_URL,
+            "interval": format!("{}s", URL_TEST_INTERVAL),
+        }));
+    }
+    for (group_name, list) in &layout.groups {
+        outbounds.push(json!({
+            "type": "selector",
+            "tag": group_name,
+            "outbounds": list,
+        }));
+    }
+    outbounds.extend(proxies);
+    outbounds.push(json!({ "type": "direct", "tag": DIRECT_TAG }));
+
+    let config = json!({
+        "log": { "level": "info" },
+        "inbounds": [{
+            "type": "mixed",
+            "tag": "mixed-in",
+            "listen": "127.0.0.1",
+            "listen_port": MIXED_LISTEN_PORT,
+        }],
+        "outbounds": outbounds,
+        "route": {
+            "final": SELECT_GROUP,
+            "auto_detect_interface": true,
+        },
+    });
+
+    serde_json::to_string_pretty(&config).map(|content| (content, skipped)).map_err(|e| format!("生成 sing-box 配置失败: {}", e))
+}
+
+// 单个节点的 outbound，sing-box 无法表示时返回 Err
+fn outbound(node: &ProxyNode, tag: &str) -> Result<Value, String> {
+    let (node_protocol, config) = protocol::node_config(node)?;
+    let username = node.username.as_deref().filter(|u| !u.is_empty());
+    let password = node.password.as_deref().filter(|p| !p.is_empty());
+
+    let mut outbound = Map::new();
+    let kind = match node_protocol {
+        Protocol::Http | Protocol::Https => "http",
+        Protocol::Socks5 => "socks",
+        Protocol::Ss => "shadowsocks",
+        // sing-box 1.6 起移除了 ShadowsocksR
+        Protocol::Ssr => return Err("sing-box 不支持 ssr 协议".to_string()),
+        Protocol::Vmess => "vmess",
+        Protocol::Vless => "vless",
+        Protocol::Trojan => "trojan",
+        Protocol::Hysteria2 => "hysteria2",
+        Protocol::Tuic => "tuic",
+    };
+    outbound.insert("type".into(), json!(kind));
+    outbound.insert("tag".into(), json!(tag));
+    // IPv6 地址不带方括号
+    outbound.insert("server".into(), json!(node.address.trim_start_matches('[').trim_end_matches(']')));
+    outbound.insert("server_port".into(), json!(node.port));
+
+    match (node_protocol, &config) {
+        (Protocol::Http | Protocol::Https | Protocol::Socks5, _) => {
+            if node_protocol == Protocol::Socks5 {
+                outbound.insert("version".into(), json!("5"));
+            }
+            if let Some(username) = username {
+                outbound.insert("username".into(), json!(username));
+            }
+            if let Some(password) = password {
+                outbound.insert("password".into(), json!(password));
+            }
+            if node_protocol == Protocol::Https {
+                outbound.insert("tls".into(), json!({ "enabled": true }));
+            }
+        }
+        (Protocol::Ss, NodeConfig::Ss(c)) => {
+            outbound.insert("method".into(), json!(c.cipher));
+            outbound.insert("password".into(), json!(password.unwrap_or("")));
+            apply_ss_plugin(&mut outbound, c)?;
+        }
+        (Protocol::Vmess, NodeConfig::Vmess(c)) => {
+            outbound.insert("uuid".into(), json!(c.uuid));
+            outbound.insert("security".into(), json!(c.security));
+            outbound.insert("alter_id".into(), json!(c.alter_id));
+            let tls = c.tls_options();
+            if tls.enabled {
+                outbound.insert("tls".into(), tls_block(&tls));
+            }
+            apply_transport(&mut outbound, &c.transport())?;
+        }
+        (Protocol::Vless, NodeConfig::Vless(c)) => {
+            outbound.insert("uuid".into(), json!(c.uuid));
+            if let Some(flow) = c.flow.as_deref().filter(|f| !f.is_empty()) {
+                outbound.insert("flow".into(), json!(flow));
+            }
+            let mut tls = c.tls_options();
+            if tls.enabled {
+                if c.is_reality() {
+                    // sing-box 的 REALITY 客户端需要启用 uTLS
+                    tls.fingerprint.get_or_insert_with(|| "chrome".to_string());
+                }
+                let mut block = tls_block(&tls);
+                if c.is_reality() {
+                    block["reality"] = json!({
+                        "enabled": true,
+                        "public_key": c.public_key.as_deref().unwrap_or(""),
+                        "short_id": c.short_id.as_deref().unwrap_or(""),
+                    });
+                }
+                outbound.insert("tls".into(), block);
+            }
+            apply_transport(&mut outbound, &c.transport())?;
+        }
+        (Protocol::Trojan, NodeConfig::Trojan(c)) => {
+            outbound.insert("password".into(), json!(password.unwrap_or("")));
+            outbound.insert("tls".into(), tls_block(&c.tls_options()));
+            apply_transport(&mut outbound, &c.transport())?;
+        }
+        (Protocol::Hysteria2, NodeConfig::Hysteria2(c)) => {
+            outbound.insert("password".into(), json!(password.unwrap_or("")));
+            if let Some(up) = c.up_mbps() {
+                outbound.insert("up_mbps".into(), json!(up));
+            }
+            if let Some(down) = c.down_mbps() {
+                outbound.insert("down_mbps".into(), json!(down));
+            }
+            if let Some(obfs) = c.obfs.as_deref().filter(|o| !o.is_empty()) {
+                outbound.insert("obfs".into(), json!({
+                    "type": obfs,
+                    "password": c.obfs_password.as_deref().unwrap_or(""),
+                }));
+            }
+            outbound.insert("tls".into(), tls_block(&c.tls_options()));
+        }
+        (Protocol::Tuic, NodeConfig::Tuic(c)) => {
+            outbound.insert("uuid".into(), json!(c.uuid));
+            outbound.insert("password".into(), json!(password.unwrap_or("")));
+            if let Some(congestion_control) = c.congestion_control.as_deref().filter(|v| !v.is_empty()) {
+                outbound.insert("congestion_control".into(), json!(congestion_control));
+            }
+            if let Some(udp_relay_mode) = c.udp_relay_mode.as_deref().filter(|v| !v.is_empty()) {
+                outbound.insert("udp_relay_mode".into(), json!(udp_relay_mode));
+            }
+            outbound.insert("tls".into(), tls_block(&c.tls_options()));
+        }
+        _ => return Err(format!("{} 节点配置与协议不符", node_protocol)),
+    }
+
+    Ok(Value::Object(outbound))
+}
+
+fn tls_block(tls: &TlsOptions) -> Value {
+    let mut block = Map::new();
+    block.insert("enabled".into(), json!(true));
+    if let Some(sni) = &tls.sni {
+        block.insert("server_name".into(), json!(sni));
+    }
+    if !tls.alpn.is_empty() {
+        block.insert("alpn".into(), json!(tls.alpn));
+    }
+    if tls.allow_insecure {
+        block.insert("insecure".into(), json!(true));
+    }
+    if let Some(fingerprint) = &tls.fingerprint {
+        block.insert("utls".into(), json!({ "enabled": true, "fingerprint": fingerprint }));
+    }
+    Value::Object(block)
+}
+
+fn apply_transport(outbound: &mut Map<String, Value>, transport: &Transport) -> Result<(), String> {
+    let mut block = Map::new();
+    match transport.network.as_str() {
+        "" | "tcp" => {
+            // sing-box 没有 tcp http 伪装
+            if let Some(header_type) = &transport.header_type {
+                return Err(format!("sing-box 不支持 tcp {} 伪装", header_type));
+            }
+            return Ok(());
+        }
+        "ws" => {
+            block.insert("type".into(), json!("ws"));
+            if let Some(path) = &transport.path {
+                block.insert("path".into(), json!(path));
+            }
+            if let Some(host) = &transport.host {
+                block.insert("headers".into(), json!({ "Host": host }));
+            }
+        }
+        "httpupgrade" => {
+            block.insert("type".into(), json!("httpupgrade"));
+            if let Some(host) = &transport.host {
+                block.insert("host".into(), json!(host));
+            }
+            if let Some(path) = &transport.path {
+                block.insert("path".into(), json!(path));
+            }
+        }
+        "h2" | "http" => {
+            block.insert("type".into(), json!("http"));
+            if let Some(host) = &transport.host {
+                block.insert("host".into(), json!([host]));
+            }
+            if let Some(path) = &transport.path {
+                block.insert("path".into(), json!(path));
+            }
+        }
+        "grpc" => {
+            block.insert("type".into(), json!("grpc"));
+            if let Some(service_name) = &transport.service_name {
+                block.insert("service_name".into(), json!(service_name));
+            }
+        }
+        other => return Err(format!("sing-box 不支持 {} 传输", other)),
+    }
+    outbound.insert("transport".into(), Value::Object(block));
+    Ok(())
+}
+
+// sing-box 只内置 obfs-local 与 v2ray-plugin 两种 SIP003 插件
+fn apply_ss_plugin(outbound: &mut Map<String, Value>, config: &SsConfig) -> Result<(), String> {
+    let Some((name, options)) = config.plugin_options() else {
+        return Ok(());
+    };
+
+    let name = match name.as_str() {
+        "obfs-local" | "simple-obfs" | "obfs" => "obfs-local",
+        "v2ray-plugin" => "v2ray-plugin",
+        other => return Err(format!("sing-box 不支持 ss 插件 {}", other)),
+    };
+    let plugin_opts: Vec<String> = options
+        .iter()
+        .map(|(key, value)| if value == "true" { key.clone() } else { format!("{}={}", key, value) })
+        .collect();
+
+    outbound.insert("plugin".into(), json!(name));
+    outbound.insert("plugin_opts".into(), json!(plugin_opts.join(";")));
+    Ok(())
+}
+
+// 导入 sing-box 出站：direct / block / dns 与代理组出站忽略，不支持的类型记入 unsupported，
+// 配置无效的出站直接忽略
+pub fn parse_outbound(outbound: &Map<String, Value>, parsed: &mut ParsedSubscription) {
+    let kind = json_str(outbound, "type").unwrap_or_default();
+    let tag = json_str(outbound, "tag").unwrap_or_default();
+    let tls = json_object(outbound, "tls").map(singbox_tls).unwrap_or_default();
+
+    let node_protocol = match kind.as_str() {
+        "direct" | "block" | "dns" | "selector" | "urltest" => return,
+        "http" if tls.enabled => Protocol::Https,
+        "http" => Protocol::Http,
+        "socks" if json_str(outbound, "version").is_none_or(|v| v == "5") => Protocol::Socks5,
+        "shadowsocks" => Protocol::Ss,
+        "vmess" => Protocol::Vmess,
+        "vless" => Protocol::Vless,
+        "trojan" => Protocol::Trojan,
+        "hysteria2" => Protocol::Hysteria2,
+        "tuic" => Protocol::Tuic,
+        _ => {
+            parsed.unsupported.push(UnsupportedEntry { name: tag, kind });
+            return;
+        }
+    };
+
+    let transport = json_object(outbound, "transport").map(singbox_transport).unwrap_or_default();
+    let config = match node_protocol {
+        Protocol::Ss => NodeConfig::Ss(SsConfig {
+            cipher: json_str(outbound, "method").unwrap_or_default(),
+            // 转换为 SIP003 插件字符串
+            plugin: json_str(outbound, "plugin").map(|plugin| match json_str(outbound, "plugin_opts") {
+                Some(opts) => format!("{};{}", plugin, opts),
+                None => plugin,
+            }),
+        }),
+        Protocol::Vmess => NodeConfig::Vmess(VmessConfig::from_parts(
+            json_str(outbound, "uuid").unwrap_or_default(),
+            json_str(outbound, "alter_id").and_then(|a| a.parse().ok()).unwrap_or(0),
+            json_str(outbound, "security").unwrap_or_default(),
+            transport,
+            tls,
+        )),
+        Protocol::Vless => {
+            let reality = json_object(outbound, "tls")
+                .and_then(|tls| json_object(tls, "reality"))
+                .filter(|reality| json_bool(reality, "enabled"));
+            let mut config = VlessConfig {
+                uuid: json_str(outbound, "uuid").unwrap_or_default(),
+                flow: json_str(outbound, "flow"),
+                security: if reality.is_some() { "reality" } else { "none" }.to_string(),
+                public_key: reality.and_then(|r| json_str(r, "public_key")),
+                short_id: reality.and_then(|r| json_str(r, "short_id")),
+                ..Default::default()
+            };
+            config.set_parts(transport, tls);
+            NodeConfig::Vless(config)
+        }
+        Protocol::Trojan => NodeConfig::Trojan(TrojanConfig::from_parts(transport, tls)),
+        Protocol::Hysteria2 => {
+            let obfs = json_object(outbound, "obfs");
+            NodeConfig::Hysteria2(Hysteria2Config {
+                sni: tls.sni,
+                alpn: tls.alpn,
+                allow_insecure: tls.allow_insecure,
+                obfs: obfs.and_then(|o| json_str(o, "type")),
+                obfs_password: obfs.and_then(|o| json_str(o, "password")),
+                up: json_str(outbound, "up_mbps").map(|up| format!("{} Mbps", up)),
+                down: json_str(outbound, "down_mbps").map(|down| format!("{} Mbps", down)),
+                ..Default::default()
+            })
+        }
+        Protocol::Tuic => NodeConfig::Tuic(TuicConfig {
+            uuid: json_str(outbound, "uuid").unwrap_or_default(),
+            sni: tls.sni,
+            alpn: tls.alpn,
+            allow_insecure: tls.allow_insecure,
+            congestion_control: json_str(outbound, "congestion_control"),
+            udp_relay_mode: json_str(outbound, "udp_relay_mode"),
+            ..Default::default()
+        }),
+        _ => NodeConfig::Plain,
+    };
+
+    if let Ok(node) = ParsedNode::build(
+        tag,
+        node_protocol,
+        json_str(outbound, "server").unwrap_or_default(),
+        json_str(outbound, "server_port").and_then(|p| p.parse().ok()).unwrap_or(0),
+        json_str(outbound, "username"),
+        json_str(outbound, "password"),
+        config,
+    ) {
+        parsed.nodes.push(node);
+    }
+}
+
+fn singbox_tls(tls: &Map<String, Value>) -> TlsOptions {
+    TlsOptions {
+        enabled: json_bool(tls, "enabled"),
+        sni: json_str(tls, "server_name"),
+        alpn: json_list(tls, "alpn"),
+        allow_insecure: json_bool(tls, "insecure"),
+        fingerprint: json_object(tls, "utls")
+            .filter(|utls| json_bool(utls, "enabled"))
+            .and_then(|utls| json_str(utls, "fingerprint")),
+    }
+}
+
+// sing-box 的 http 传输对应 v2ray 的 h2
+fn singbox_transport(transport: &Map<String, Value>) -> Transport {
+    let kind = json_str(transport, "type").unwrap_or_default();
+    let mut result = Transport {
+        network: if kind == "http" { "h2".to_string() } else { kind.clone() },
+        path: json_str(transport, "path"),
+        ..Default::default()
+    };
+    match kind.as_str() {
+        "ws" => {
+            result.host = json_object(transport, "headers").and_then(|headers| json_list(headers, "Host").into_iter().next());
+        }
+        "http" | "httpupgrade" => result.host = json_list(transport, "host").into_iter().next(),
+        "grpc" => result.service_name = json_str(transport, "service_name"),
+        _ => {}
+    }
+    result
+}
@@ -38,3 +38,135 @@ pub fn extract_token(req: &Request) -> Option<String> {
             }
         })
 }
+
+// 将 Unix 时间戳（秒）格式化为 SQLite datetime() 使用的 UTC 格式: YYYY-MM-DD HH:MM:SS
+pub fn format_sqlite_datetime(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86400);
+    let secs = timestamp.rem_euclid(86400);
+
+    // 公历日期换算 (days since 1970-01-01 -> y/m/d)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, secs / 3600, secs % 3600 / 60, secs % 60
+    )
+}
+
+// 当前时间往前 seconds 秒，SQLite datetime 格式
+pub fn sqlite_datetime_ago(seconds: i64) -> String {
+    let now = (js_sys::Date::now() / 1000.0) as i64;
+    format_sqlite_datetime(now - seconds)
+}
+
+// Base64 解码
+pub fn base64_decode(input: &str) -> Option<String> {
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+    
+    // 尝试标准 Base64
+    if let Ok(bytes) = STANDARD.decode(input) {
+        if let Ok(s) = String::from_utf8(bytes) {
+            return Some(s);
+        }
+    }
+    
+    // 尝试 URL-safe Base64
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    if let Ok(bytes) = URL_SAFE_NO_PAD.decode(input) {
+        if let Ok(s) = String::from_utf8(bytes) {
+            return Some(s);
+        }
+    }
+    
+    None
+}
+
+// URL 解码 - 正确处理 UTF-8 多字节字符
+pub fn urlencoding_decode(input: &str) -> String {
+    decode_percent(input, true)
+}
+
+// 百分号解码，"+" 保持原样，用于链接中的用户名、密码
+pub fn percent_decode(input: &str) -> String {
+    decode_percent(input, false)
+}
+
+fn decode_percent(input: &str, plus_as_space: bool) -> String {
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut chars = input.chars().peekable();
+    
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            let hex: String = chars.by_ref().take(2).collect();
+            if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                bytes.push(byte);
+            } else {
+                bytes.push(b'%');
+                bytes.extend(hex.as_bytes());
+            }
+        } else if c == '+' && plus_as_space {
+            bytes.push(b' ');
+        } else {
+            // 对于普通字符，将其 UTF-8 编码添加到字节数组
+            let mut buf = [0u8; 4];
+            let encoded = c.encode_utf8(&mut buf);
+            bytes.extend(encoded.as_bytes());
+        }
+    }
+    
+    // 将字节数组转换为 UTF-8 字符串
+    String::from_utf8(bytes).unwrap_or_else(|_| input.to_string())
+}
+
+// 百分号编码，仅保留 RFC 3986 中的非保留字符
+pub fn percent_encode(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// 读取 JSON 对象中的字符串值，数字与布尔值转为字符串，空字符串视为不存在
+pub fn json_str(map: &serde_json::Map<String, serde_json::Value>, key: &str) -> Option<String> {
+    match map.get(key)? {
+        serde_json::Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+pub fn json_bool(map: &serde_json::Map<String, serde_json::Value>, key: &str) -> bool {
+    json_str(map, key).is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+// 兼容字符串数组与逗号分隔的字符串
+pub fn json_list(map: &serde_json::Map<String, serde_json::Value>, key: &str) -> Vec<String> {
+    match map.get(key) {
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect(),
+        _ => json_str(map, key)
+            .map(|value| value.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default(),
+    }
+}
+
+pub fn json_object<'a>(map: &'a serde_json::Map<String, serde_json::Value>, key: &str) -> Option<&'a serde_json::Map<String, serde_json::Value>> {
+    map.get(key)?.as_object()
+}
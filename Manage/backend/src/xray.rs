@@ -0,0 +1,170 @@
+// 导入 Xray / V2Ray 配置中的代理出站（outbounds[].protocol / settings / streamSettings）
+
+use serde_json::{Map, Value};
+use crate::protocol::{NodeConfig, Protocol, SsConfig, TlsOptions, Transport, TrojanConfig, VlessConfig, VmessConfig};
+use crate::share_link::{ParsedNode, ParsedSubscription, UnsupportedEntry};
+use crate::utils::{json_bool, json_list, json_object, json_str};
+
+// freedom / blackhole / dns / loopback 出站忽略，不支持的协议记入 unsupported，
+// 配置无效的服务器直接忽略；一个出站包含多个服务器时逐个导入
+pub fn parse_outbound(outbound: &Map<String, Value>, parsed: &mut ParsedSubscription) {
+    let kind = json_str(outbound, "protocol").unwrap_or_default();
+    let tag = json_str(outbound, "tag").unwrap_or_default();
+    let stream = json_object(outbound, "streamSettings");
+    let tls = stream.map(xray_tls).unwrap_or_default();
+
+    let node_protocol = match kind.as_str() {
+        "freedom" | "blackhole" | "dns" | "loopback" => return,
+        "http" if tls.enabled => Protocol::Https,
+        "http" => Protocol::Http,
+        "socks" => Protocol::Socks5,
+        "shadowsocks" => Protocol::Ss,
+        "vmess" => Protocol::Vmess,
+        "vless" => Protocol::Vless,
+        "trojan" => Protocol::Trojan,
+        _ => {
+            parsed.unsupported.push(UnsupportedEntry { name: tag, kind });
+            return;
+        }
+    };
+
+    // vmess / vless 的服务器在 vnext 中，其余协议在 servers 中
+    let servers_key = if matches!(node_protocol, Protocol::Vmess | Protocol::Vless) { "vnext" } else { "servers" };
+    let servers: Vec<&Map<String, Value>> = json_object(outbound, "settings")
+        .and_then(|settings| settings.get(servers_key))
+        .and_then(Value::as_array)
+        .map(|servers| servers.iter().filter_map(Value::as_object).collect())
+        .unwrap_or_default();
+
+    for (index, server) in servers.iter().enumerate() {
+        let name = if servers.len() > 1 { format!("{} {}", tag, index + 1) } else { tag.clone() };
+        let user = server
+            .get("users")
+            .and_then(Value::as_array)
+            .and_then(|users| users.first())
+            .and_then(Value::as_object);
+        let user_str = |key: &str| user.and_then(|u| json_str(u, key));
+
+        let (username, password, config) = match node_protocol {
+            Protocol::Ss => (None, json_str(server, "password"), NodeConfig::Ss(SsConfig {
+                cipher: json_str(server, "method").unwrap_or_default(),
+                plugin: None,
+            })),
+            Protocol::Vmess => (None, None, NodeConfig::Vmess(VmessConfig::from_parts(
+                user_str("id").unwrap_or_default(),
+                user_str("alterId").and_then(|a| a.parse().ok()).unwrap_or(0),
+                user_str("security").unwrap_or_default(),
+                stream.map(xray_transport).unwrap_or_default(),
+                tls.clone(),
+            ))),
+            Protocol::Vless => {
+                let reality = stream
+                    .filter(|st| json_str(st, "security").as_deref() == Some("reality"))
+                    .and_then(|st| json_object(st, "realitySettings"));
+                let mut config = VlessConfig {
+                    uuid: user_str("id").unwrap_or_default(),
+                    flow: user_str("flow"),
+                    security: if reality.is_some() { "reality" } else { "none" }.to_string(),
+                    public_key: reality.and_then(|r| json_str(r, "publicKey")),
+                    short_id: reality.and_then(|r| json_str(r, "shortId")),
+                    spider_x: reality.and_then(|r| json_str(r, "spiderX")),
+                    ..Default::default()
+                };
+                config.set_parts(stream.map(xray_transport).unwrap_or_default(), tls.clone());
+                (None, None, NodeConfig::Vless(config))
+            }
+            Protocol::Trojan => (None, json_str(server, "password"), NodeConfig::Trojan(TrojanConfig::from_parts(
+                stream.map(xray_transport).unwrap_or_default(),
+                tls.clone(),
+            ))),
+            _ => (user_str("user"), user_str("pass"), NodeConfig::Plain),
+        };
+
+        if let Ok(node) = ParsedNode::build(
+            name,
+            node_protocol,
+            json_str(server, "address").unwrap_or_default(),
+            json_str(server, "port").and_then(|p| p.parse().ok()).unwrap_or(0),
+            username,
+            password,
+            config,
+        ) {
+            parsed.nodes.push(node);
+        }
+    }
+}
+
+// REALITY 的 serverName 与指纹在 realitySettings 中
+fn xray_tls(stream: &Map<String, Value>) -> TlsOptions {
+    let settings = match json_str(stream, "security").as_deref() {
+        Some("tls") => json_object(stream, "tlsSettings"),
+        Some("reality") => json_object(stream, "realitySettings"),
+        _ => return TlsOptions::default(),
+    };
+    let setting = |key: &str| settings.and_then(|s| json_str(s, key));
+    TlsOptions {
+        enabled: true,
+        sni: setting("serverName"),
+        alpn: settings.map(|s| json_list(s, "alpn")).unwrap_or_default(),
+        allow_insecure: settings.is_some_and(|s| json_bool(s, "allowInsecure")),
+        fingerprint: setting("fingerprint"),
+    }
+}
+
+// Xray 新版本中 tcp 传输改名为 raw；http 传输即 h2
+fn xray_transport(stream: &Map<String, Value>) -> Transport {
+    let network = match json_str(stream, "network").as_deref() {
+        None | Some("raw") => "tcp".to_string(),
+        Some("http") => "h2".to_string(),
+        Some(other) => other.to_string(),
+    };
+    let mut transport = Transport { network, ..Default::default() };
+
+    match transport.network.as_str() {
+        "tcp" => {
+            let header = json_object(stream, "tcpSettings")
+                .or_else(|| json_object(stream, "rawSettings"))
+                .and_then(|settings| json_object(settings, "header"));
+            if let Some(header) = header.filter(|h| json_str(h, "type").as_deref() == Some("http")) {
+                let request = json_object(header, "request");
+                transport.header_type = Some("http".to_string());
+                transport.path = request.and_then(|r| json_list(r, "path").into_iter().next());
+                transport.host = request
+                    .and_then(|r| json_object(r, "headers"))
+                    .and_then(|headers| json_list(headers, "Host").into_iter().next());
+            }
+        }
+        "ws" => {
+            if let Some(settings) = json_object(stream, "wsSettings") {
+                transport.path = json_str(settings, "path");
+                transport.host = json_str(settings, "host").or_else(|| {
+                    json_object(settings, "headers").and_then(|headers| json_str(headers, "Host"))
+                });
+            }
+        }
+        "httpupgrade" => {
+            if let Some(settings) = json_object(stream, "httpupgradeSettings") {
+                transport.path = json_str(settings, "path");
+                transport.host = json_str(settings, "host");
+            }
+        }
+        "h2" => {
+            if let Some(settings) = json_object(stream, "httpSettings") {
+                transport.path = json_str(settings, "path");
+                transport.host = json_list(settings, "host").into_iter().next();
+            }
+        }
+        "xhttp" | "splithttp" => {
+            let settings = json_object(stream, "xhttpSettings").or_else(|| json_object(stream, "splithttpSettings"));
+            if let Some(settings) = settings {
+                transport.path = json_str(settings, "path");
+                transport.host = json_str(settings, "host");
+            }
+        }
+        "grpc" => {
+            transport.service_name = json_object(stream, "grpcSettings").and_then(|settings| json_str(settings, "serviceName"));
+        }
+        _ => {}
+    }
+    transport
+}
@@ -10,12 +10,44 @@ export interface ProxyNode {
     username?: string
     password?: string
     extra_config?: string
+    // 所在分组 id，未分组时为空
+    group_id?: number | null
     group_name?: string
     status: string
     latency?: number
     last_check_at?: string
+    last_error?: string
+    stale_at?: string
+    tags: string[]
+    region?: string
+    enabled: boolean
+    sort_order: number
     created_at: string
     updated_at: string
+    uptime_24h?: number
+}
+
+// 从协议配置中解析出的传输参数
+export interface NodeTransport {
+    network: string
+    host?: string
+    path?: string
+    service_name?: string
+    header_type?: string
+}
+
+export interface NodeTls {
+    enabled: boolean
+    sni?: string
+    alpn: string[]
+    allow_insecure: boolean
+    fingerprint?: string
+}
+
+// 节点详情，不含传输层或 TLS 配置的协议对应字段为空
+export interface ProxyNodeDetail extends ProxyNode {
+    transport?: NodeTransport | null
+    tls?: NodeTls | null
 }
 
 export interface CreateProxyNodeRequest {
@@ -27,6 +59,9 @@ export interface CreateProxyNodeRequest {
     password?: string
     extra_config?: string
     group_name?: string
+    tags?: string[]
+    // 未提供时从名称识别，空字符串表示不设置地区
+    region?: string
 }
 
 export interface ProxyCheckResult {
@@ -36,17 +71,148 @@ export interface ProxyCheckResult {
     message: string
 }
 
+export interface ProxyNodeFilter {
+    group_name?: string
+    protocol?: string
+    status?: string
+    search?: string
+    max_latency?: number
+    ids?: number[]
+    region?: string
+    tags?: string[]
+    enabled?: boolean
+}
+
+export type ProxyNodeSort = 'id_desc' | 'id_asc' | 'name_asc' | 'name_desc' | 'latency_asc' | 'latency_desc' | 'manual'
+
+export interface ListNodesParams extends Omit<ProxyNodeFilter, 'ids' | 'tags'> {
+    ids?: string
+    // 逗号分隔，节点需同时包含全部标签
+    tags?: string
+    sort?: ProxyNodeSort
+    limit?: number
+    cursor?: string
+}
+
+export interface FacetCount {
+    value: string
+    count: number
+}
+
+export interface ProxyNodeList {
+    items: ProxyNode[]
+    total: number
+    next_cursor?: string
+    facets: {
+        protocol: FacetCount[]
+        status: FacetCount[]
+        group_name: FacetCount[]
+        region: FacetCount[]
+        tags: FacetCount[]
+    }
+}
+
+export interface BatchCheckResult {
+    total: number
+    checked: number
+    partial: boolean
+    skipped_ids: number[]
+    results: ProxyCheckResult[]
+}
+
 export interface ImportResult {
     total: number
-    success: number
+    added: number
+    updated: number
+    unchanged: number
+    duplicates: number
     failed: number
     nodes: string[]
+    // 客户端配置中不支持的代理类型，未导入
+    unsupported: UnsupportedEntry[]
+}
+
+export interface UnsupportedEntry {
+    name: string
+    kind: string
+}
+
+export interface SubscriptionRefreshResult {
+    group_name: string
+    total: number
+    added: number
+    updated: number
+    unchanged: number
+    vanished: number
+    removed: boolean
+    failed: number
 }
 
 export interface BatchDeleteResult {
     deleted: number
 }
 
+export type BatchOperation =
+    | { type: 'move_group'; group_id: number | null }
+    | { type: 'rename'; pattern: string; replacement: string }
+    | { type: 'replace_address'; address?: string; port?: number }
+    | { type: 'check' }
+
+// ids 与 filter 至少提供一个
+export interface BatchUpdateRequest {
+    ids?: number[]
+    filter?: ProxyNodeFilter
+    operation: BatchOperation
+}
+
+export interface BatchUpdateResult {
+    operation: BatchOperation['type']
+    total: number
+    updated: number
+    failed: number
+    results: { id: number; name?: string; success: boolean; changed: boolean; message?: string }[]
+}
+
+export interface MigrateConfigResult {
+    total: number
+    migrated: number
+    unchanged: number
+    failed: { id: number; name: string; error: string }[]
+    // 此前已全部迁移成功时为上次完成时间，本次未执行
+    completed_at?: string | null
+}
+
+// extra_config 中保存的各协议配置（JSON）
+export interface VmessConfig {
+    uuid: string
+    alter_id: number
+    security: string
+    network: string
+    tls: boolean
+    host?: string | null
+    path?: string | null
+    service_name?: string | null
+    header_type?: string | null
+    [key: string]: unknown
+}
+
+export interface TrojanConfig {
+    sni?: string
+    alpn: string[]
+    allow_insecure: boolean
+    network: string
+    host?: string | null
+    path?: string | null
+    service_name?: string | null
+    header_type?: string | null
+    [key: string]: unknown
+}
+
+export interface SsConfig {
+    cipher: string
+    plugin?: string
+}
+
 export interface SubscriptionInfo {
     id: number
     group_name: string
@@ -57,16 +223,101 @@ export interface SubscriptionInfo {
     expire_timestamp?: number
     last_update_at?: string
     created_at: string
+    refresh_enabled: boolean
+    refresh_interval_minutes: number
+    last_refresh_at?: string
+    last_refresh_status?: 'success' | 'error'
+    last_refresh_added?: number
+    last_refresh_updated?: number
+    last_refresh_vanished?: number
+    last_refresh_error?: string
+}
+
+export interface NodeGroup {
+    id: number
+    name: string
+    description?: string
+    color?: string
+    sort_order: number
+    node_count: number
+    created_at: string
+    updated_at: string
+}
+
+export interface NodeGroupRequest {
+    name: string
+    description?: string
+    color?: string
+    sort_order?: number
+}
+
+export type ExportFormat = 'uri' | 'base64' | 'clash' | 'singbox' | 'surge' | 'quanx' | 'loon'
+
+export interface ExportParams {
+    format: ExportFormat
+    group_name?: string
+    // 逗号分隔
+    tags?: string
+    region?: string
+    protocol?: string
+    ids?: string
+}
+
+export interface ProxyNodeCheck {
+    status: string
+    latency?: number
+    message?: string
+    checked_at: string
+}
+
+export interface ProxyNodeStats {
+    node_id: number
+    window_hours: number
+    total_checks: number
+    valid_checks: number
+    uptime?: number
+    // 延迟统计实际覆盖的小时数，不超过原始检测记录的保留期
+    latency_window_hours: number
+    avg_latency?: number
+    p50_latency?: number
+    p95_latency?: number
+    timeline: ProxyNodeCheck[]
+}
+
+export interface GroupCheckSetting {
+    group_name: string
+    check_interval_minutes: number
+    updated_at?: string
+}
+
+export interface CheckSettings {
+    default_interval_minutes: number
+    groups: GroupCheckSetting[]
 }
 
 export const proxyApi = {
-    async listNodes(): Promise<ApiResponse<ProxyNode[]>> {
-        const response = await api.get<ApiResponse<ProxyNode[]>>('/proxy/nodes')
+    async listNodes(params: ListNodesParams = {}): Promise<ApiResponse<ProxyNodeList>> {
+        const response = await api.get<ApiResponse<ProxyNodeList>>('/proxy/nodes', { params })
         return response.data
     },
 
-    async getNode(id: number): Promise<ApiResponse<ProxyNode>> {
-        const response = await api.get<ApiResponse<ProxyNode>>(`/proxy/nodes/${id}`)
+    // 按 cursor 逐页拉取全部节点
+    async listAllNodes(params: Omit<ListNodesParams, 'cursor' | 'limit'> = {}): Promise<ApiResponse<ProxyNode[]>> {
+        const items: ProxyNode[] = []
+        let cursor: string | undefined
+        do {
+            const response = await this.listNodes({ ...params, limit: 500, cursor })
+            if (!response.success || !response.data) {
+                return { ...response, data: undefined }
+            }
+            items.push(...response.data.items)
+            cursor = response.data.next_cursor
+        } while (cursor)
+        return { success: true, data: items, message: 'Success' }
+    },
+
+    async getNode(id: number): Promise<ApiResponse<ProxyNodeDetail>> {
+        const response = await api.get<ApiResponse<ProxyNodeDetail>>(`/proxy/nodes/${id}`)
         return response.data
     },
 
@@ -85,13 +336,36 @@ export const proxyApi = {
         return response.data
     },
 
+    async getNodeLink(id: number): Promise<ApiResponse<{ link: string }>> {
+        const response = await api.get<ApiResponse<{ link: string }>>(`/proxy/nodes/${id}/link`)
+        return response.data
+    },
+
+    // 导出为纯文本，skipped 为配置无效未导出的节点 id
+    async exportNodes(params: ExportParams): Promise<{ content: string; skipped: number[]; unsupported: UnsupportedEntry[] }> {
+        const response = await api.get<string>('/proxy/export', { params, responseType: 'text' })
+        const skipped = String(response.headers['x-skipped-nodes'] || '')
+            .split(',')
+            .filter(Boolean)
+            .map(Number)
+        // 因 ss 插件不受支持而跳过的节点，JSON 经百分号编码
+        const unsupportedHeader = response.headers['x-unsupported-nodes']
+        const unsupported: UnsupportedEntry[] = unsupportedHeader ? JSON.parse(decodeURIComponent(String(unsupportedHeader))) : []
+        return { content: response.data, skipped, unsupported }
+    },
+
     async checkNode(id: number): Promise<ApiResponse<ProxyCheckResult>> {
         const response = await api.post<ApiResponse<ProxyCheckResult>>(`/proxy/nodes/${id}/check`)
         return response.data
     },
 
-    async checkAllNodes(): Promise<ApiResponse<ProxyCheckResult[]>> {
-        const response = await api.post<ApiResponse<ProxyCheckResult[]>>('/proxy/nodes/check-all')
+    async getNodeStats(id: number, hours = 24): Promise<ApiResponse<ProxyNodeStats>> {
+        const response = await api.get<ApiResponse<ProxyNodeStats>>(`/proxy/nodes/${id}/stats`, { params: { hours } })
+        return response.data
+    },
+
+    async checkAllNodes(filter: ProxyNodeFilter = {}): Promise<ApiResponse<BatchCheckResult>> {
+        const response = await api.post<ApiResponse<BatchCheckResult>>('/proxy/nodes/check-all', filter)
         return response.data
     },
 
@@ -105,9 +379,83 @@ export const proxyApi = {
         return response.data
     },
 
+    async batchUpdateNodes(data: BatchUpdateRequest): Promise<ApiResponse<BatchUpdateResult>> {
+        const response = await api.post<ApiResponse<BatchUpdateResult>>('/proxy/nodes/batch-update', data)
+        return response.data
+    },
+
+    async migrateNodeConfigs(force = false): Promise<ApiResponse<MigrateConfigResult>> {
+        const response = await api.post<ApiResponse<MigrateConfigResult>>('/proxy/nodes/migrate-config', undefined, {
+            params: force ? { force: true } : undefined
+        })
+        return response.data
+    },
+
+    async setNodeEnabled(id: number, enabled: boolean): Promise<ApiResponse<void>> {
+        const response = await api.put<ApiResponse<void>>(`/proxy/nodes/${id}/enabled`, { enabled })
+        return response.data
+    },
+
+    // ids 为分组内拖动后的节点顺序
+    async reorderNodes(groupName: string, ids: number[]): Promise<ApiResponse<void>> {
+        const response = await api.post<ApiResponse<void>>('/proxy/nodes/reorder', { group_name: groupName, ids })
+        return response.data
+    },
+
+    async listGroups(): Promise<ApiResponse<NodeGroup[]>> {
+        const response = await api.get<ApiResponse<NodeGroup[]>>('/proxy/groups')
+        return response.data
+    },
+
+    async createGroup(data: NodeGroupRequest): Promise<ApiResponse<{ id: number }>> {
+        const response = await api.post<ApiResponse<{ id: number }>>('/proxy/groups', data)
+        return response.data
+    },
+
+    async updateGroup(id: number, data: NodeGroupRequest): Promise<ApiResponse<void>> {
+        const response = await api.put<ApiResponse<void>>(`/proxy/groups/${id}`, data)
+        return response.data
+    },
+
+    // mode=cascade 连同节点一起删除，mode=move 将节点移动到 targetId（未指定时移出分组）
+    async deleteGroup(id: number, mode: 'cascade' | 'move', targetId?: number): Promise<ApiResponse<void>> {
+        const response = await api.delete<ApiResponse<void>>(`/proxy/groups/${id}`, {
+            params: { mode, ...(targetId !== undefined ? { target_id: targetId } : {}) },
+        })
+        return response.data
+    },
+
+    async moveNodes(ids: number[], groupId: number | null): Promise<ApiResponse<{ moved: number }>> {
+        const response = await api.post<ApiResponse<{ moved: number }>>('/proxy/nodes/move', { ids, group_id: groupId })
+        return response.data
+    },
+
     async listSubscriptionInfo(): Promise<ApiResponse<SubscriptionInfo[]>> {
         const response = await api.get<ApiResponse<SubscriptionInfo[]>>('/proxy/subscriptions')
         return response.data
     },
-}
 
+    async refreshSubscription(groupName: string, removeMissing = false): Promise<ApiResponse<SubscriptionRefreshResult>> {
+        const response = await api.post<ApiResponse<SubscriptionRefreshResult>>(
+            `/proxy/subscriptions/${encodeURIComponent(groupName)}/refresh`,
+            undefined,
+            { params: removeMissing ? { remove_missing: true } : undefined }
+        )
+        return response.data
+    },
+
+    async updateSubscriptionSetting(groupName: string, data: { refresh_enabled: boolean; refresh_interval_minutes: number }): Promise<ApiResponse<void>> {
+        const response = await api.put<ApiResponse<void>>(`/proxy/subscriptions/${encodeURIComponent(groupName)}/settings`, data)
+        return response.data
+    },
+
+    async getCheckSettings(): Promise<ApiResponse<CheckSettings>> {
+        const response = await api.get<ApiResponse<CheckSettings>>('/proxy/check-settings')
+        return response.data
+    },
+
+    async updateCheckSetting(data: { group_name: string; check_interval_minutes: number }): Promise<ApiResponse<void>> {
+        const response = await api.put<ApiResponse<void>>('/proxy/check-settings', data)
+        return response.data
+    },
+}